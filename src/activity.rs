@@ -0,0 +1,61 @@
+use cosmwasm_std::{Addr, Storage, Uint128};
+
+use crate::{
+    error::ContractError,
+    math::{add_u128, add_u64},
+    msg::AccountStats,
+    state::{ACCOUNT_STATS, ORDERED_SENDERS},
+};
+
+fn load_account_stats(
+    store: &dyn Storage,
+    address: &Addr,
+) -> Result<AccountStats, ContractError> {
+    Ok(ACCOUNT_STATS.may_load(store, address)?.unwrap_or_default())
+}
+
+/// Add the transfer to the lifetime totals of both accounts and move the
+/// sender up in the sender ranking.
+pub fn record_transfer(
+    store: &mut dyn Storage,
+    sender: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let mut sender_stats = load_account_stats(store, sender)?;
+    ORDERED_SENDERS.remove(store, (sender_stats.n_sent.u64(), sender));
+    sender_stats.amount_sent = add_u128(sender_stats.amount_sent, amount)?;
+    sender_stats.n_sent = add_u64(sender_stats.n_sent, 1u64)?;
+    ORDERED_SENDERS.save(store, (sender_stats.n_sent.u64(), sender), &0)?;
+    ACCOUNT_STATS.save(store, sender, &sender_stats)?;
+
+    let mut recipient_stats = load_account_stats(store, recipient)?;
+    recipient_stats.amount_received = add_u128(recipient_stats.amount_received, amount)?;
+    recipient_stats.n_received = add_u64(recipient_stats.n_received, 1u64)?;
+    ACCOUNT_STATS.save(store, recipient, &recipient_stats)?;
+
+    Ok(())
+}
+
+/// Minted tokens count toward the recipient's amount received.
+pub fn record_mint(
+    store: &mut dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let mut stats = load_account_stats(store, recipient)?;
+    stats.amount_received = add_u128(stats.amount_received, amount)?;
+    ACCOUNT_STATS.save(store, recipient, &stats)?;
+    Ok(())
+}
+
+pub fn record_burn(
+    store: &mut dyn Storage,
+    burner: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let mut stats = load_account_stats(store, burner)?;
+    stats.amount_burned = add_u128(stats.amount_burned, amount)?;
+    ACCOUNT_STATS.save(store, burner, &stats)?;
+    Ok(())
+}
@@ -1,11 +1,13 @@
 use crate::{
     error::ContractError,
-    state::{FROZEN_ACCOUNTS, OPERATOR_ADDR},
+    state::{FROZEN_ACCOUNTS, OPERATOR_ADDR, REINDEX_STATE},
 };
 use cosmwasm_std::{ensure_eq, ensure_ne, Addr, Storage};
 
 use crate::state::GLOBAL_BALANCE_FREEZE;
 
+const MAX_MEMO_LENGTH: usize = 256;
+
 /// Forbid self-transfers, which is something that can be abused to implement
 /// certain types of attacks.
 pub fn ensure_not_self_transfer(
@@ -57,6 +59,18 @@ pub fn ensure_accounts_not_frozen(
     Ok(())
 }
 
+/// Keep transfer memos short enough to store in transfer history.
+pub fn ensure_valid_memo(memo: &Option<String>) -> Result<(), ContractError> {
+    if let Some(memo) = memo {
+        if memo.len() > MAX_MEMO_LENGTH {
+            return Err(ContractError::ValidationError {
+                reason: format!("memo cannot exceed {} bytes", MAX_MEMO_LENGTH),
+            });
+        }
+    }
+    Ok(())
+}
+
 pub fn ensure_operator(
     store: &dyn Storage,
     addr: &Addr,
@@ -70,3 +84,14 @@ pub fn ensure_operator(
     );
     Ok(())
 }
+
+/// Ensure that no Reindex is in progress. Until it finishes, the ordered balance
+/// index and the counts and stats derived from it are incomplete.
+pub fn ensure_index_ready(store: &dyn Storage) -> Result<(), ContractError> {
+    if REINDEX_STATE.exists(store) {
+        return Err(ContractError::ValidationError {
+            reason: "balance index is being rebuilt; retry once Reindex completes".to_owned(),
+        });
+    }
+    Ok(())
+}
@@ -1,15 +1,31 @@
 use crate::checks::ensure_operator;
 use crate::error::ContractError;
+use crate::execute::after_burn::after_burn;
+use crate::execute::after_mint::after_mint;
+use crate::execute::after_transfer::after_transfer;
+use crate::execute::after_update_allowance::after_update_allowance;
 use crate::execute::before_burn::before_burn;
 use crate::execute::before_mint::before_mint;
 use crate::execute::before_transfer::before_transfer;
 use crate::execute::before_update_marketing::before_update_marketing;
 use crate::execute::before_upload_logo::before_upload_logo;
 use crate::execute::operator::copy_cw20_balances::exec_copy_cw20_balances;
+use crate::execute::operator::enable_balance_change_listener::exec_enable_balance_change_listener;
+use crate::execute::operator::cancel_copy::exec_cancel_copy;
 use crate::execute::operator::freeze::{exec_freeze, exec_unfreeze};
+use crate::execute::operator::import::exec_import;
+use crate::execute::operator::reindex::exec_reindex;
 use crate::execute::operator::remove_operator::exec_remove_operator;
 use crate::execute::operator::set_operator::exec_set_operator;
+use crate::execute::operator::set_subscription_deposit::exec_set_subscription_deposit;
+use crate::execute::operator::set_tiers::exec_set_tiers;
+use crate::execute::operator::set_transfer_history_size::exec_set_transfer_history_size;
 use crate::execute::operator::update_balance_change_listeners::exec_update_balance_change_listeners;
+use crate::execute::operator::update_excluded_accounts::exec_update_excluded_accounts;
+use crate::execute::operator::update_legacy_tokens::exec_update_legacy_tokens;
+use crate::execute::receive::exec_receive;
+use crate::execute::retry_balance_change_notifications::exec_retry_balance_change_notifications;
+use crate::execute::subscribe::{exec_allow_subscriber, exec_evict_subscriber, exec_subscribe, exec_unsubscribe};
 use crate::execute::tf::burn::exec_tf_burn;
 use crate::execute::tf::derive_balances::exec_tf_derive_balances;
 use crate::execute::tf::derive_denom::exec_tf_derive_denom;
@@ -18,12 +34,28 @@ use crate::execute::tf::remove_denom_admin::exec_tf_remove_admin;
 use crate::execute::tf::set_denom_admin::exec_tf_set_admin;
 use crate::execute::tf::set_denom_metadata::exec_tf_set_metadata;
 use crate::msg::{
-    ExecuteMsg, MigrateMsg, OperatorExecuteMsg, ProBalanceQueryMsg, ProQueryMsg, QueryMsg, TokenFactoryExecuteMsg,
+    BalanceChangeOperation, ExecuteMsg, MigrateMsg, OperatorExecuteMsg, ProBalanceQueryMsg, ProQueryMsg, QueryMsg,
+    TokenFactoryExecuteMsg,
 };
+use crate::query::activity::{query_account_stats, query_top_senders};
 use crate::query::balances::{query_balances_by_address, query_paginate_balances};
+use crate::query::history::query_history;
+use crate::query::legacy_tokens::query_legacy_tokens;
+use crate::query::audit::query_audit;
+use crate::query::copy::{query_copy_job, query_copy_preview};
+use crate::query::export::query_export;
+use crate::query::listeners::{query_listener_health, query_listener_queue, query_listeners};
+use crate::query::stats::query_stats;
+use crate::query::subscriptions::query_subscription;
+use crate::query::supply::{query_circulating_supply, query_excluded_accounts, query_holder_count};
+use crate::query::tiers::{query_account_tier, query_tier_holders, query_tiers};
+use crate::query::transfer_history::query_transfer_history;
+use crate::listeners::LISTENER_REPLY_ID_OFFSET;
+use crate::migrate::exec_migrate;
+use crate::reply::listener::reply_listener;
 use crate::state;
 use cosmwasm_std::{entry_point, to_json_binary};
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError};
 use cw2::set_contract_version;
 use cw20_base::allowances::{
     execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
@@ -35,8 +67,8 @@ use cw20_base::contract::{
 };
 use cw20_base::enumerable::{query_all_accounts, query_owner_allowances, query_spender_allowances};
 
-const CONTRACT_NAME: &str = "crates.io:cw20-pro";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const CONTRACT_NAME: &str = "crates.io:cw20-pro";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const INITIAL_TF_MINT_REPLY_ID: u64 = 1_000_000u64;
 
@@ -48,12 +80,12 @@ pub fn instantiate(
     msg: cw20_base::msg::InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(state::init(deps, env, info, msg)?)
+    state::init(deps, env, info, msg)
 }
 
 #[entry_point]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
@@ -65,14 +97,36 @@ pub fn execute(
             match msg {
                 OperatorExecuteMsg::RemoveOperator {} => exec_remove_operator(deps),
                 OperatorExecuteMsg::SetOperator { address } => exec_set_operator(deps, address),
-                OperatorExecuteMsg::UpdateBalanceChangeListeners { add, remove } => {
-                    exec_update_balance_change_listeners(deps, add, remove)
+                OperatorExecuteMsg::UpdateBalanceChangeListeners { add, remove, configs } => {
+                    exec_update_balance_change_listeners(deps, env, add, remove, configs)
+                },
+                OperatorExecuteMsg::EnableBalanceChangeListener { address } => {
+                    exec_enable_balance_change_listener(deps, address)
                 },
-                OperatorExecuteMsg::FreezeBalances { addresses } => exec_freeze(deps, addresses),
-                OperatorExecuteMsg::UnfreezeBalances { addresses } => exec_unfreeze(deps, addresses),
-                OperatorExecuteMsg::CopyBalances { cw20_address, mode } => {
-                    exec_copy_cw20_balances(deps, cw20_address, mode)
+                OperatorExecuteMsg::SetSubscriptionDeposit { deposit } => exec_set_subscription_deposit(deps, deposit),
+                OperatorExecuteMsg::UpdateExcludedAccounts { add, remove } => {
+                    exec_update_excluded_accounts(deps, add, remove)
                 },
+                OperatorExecuteMsg::UpdateLegacyTokens { add, remove } => {
+                    exec_update_legacy_tokens(deps, env, add, remove)
+                },
+                OperatorExecuteMsg::SetTiers { tiers } => exec_set_tiers(deps, tiers),
+                OperatorExecuteMsg::SetTransferHistorySize { size } => exec_set_transfer_history_size(deps, size),
+                OperatorExecuteMsg::FreezeBalances { addresses } => exec_freeze(deps, env, addresses),
+                OperatorExecuteMsg::UnfreezeBalances { addresses } => exec_unfreeze(deps, env, addresses),
+                OperatorExecuteMsg::CopyBalances {
+                    source,
+                    mode,
+                    cap_behavior,
+                    remap_prefix,
+                } => exec_copy_cw20_balances(deps, env, source, mode, cap_behavior, remap_prefix),
+                OperatorExecuteMsg::CancelCopy {} => exec_cancel_copy(deps),
+                OperatorExecuteMsg::Reindex { limit } => exec_reindex(deps, limit),
+                OperatorExecuteMsg::Import {
+                    records,
+                    checksum,
+                    done,
+                } => exec_import(deps, info, records, checksum, done),
                 // TODO: add burner whitelist. if not exist, burning is public
             }
         },
@@ -91,68 +145,160 @@ pub fn execute(
             }
         },
 
+        // Balance change listener redelivery crank
+        ExecuteMsg::RetryBalanceChangeNotifications { listener, limit } => {
+            exec_retry_balance_change_notifications(deps, env, listener, limit)
+        },
+
+        // Self-service balance change subscriptions
+        ExecuteMsg::Subscribe {
+            watched,
+            event_version,
+            gas_limit,
+        } => exec_subscribe(deps, info, watched, event_version, gas_limit),
+        ExecuteMsg::Unsubscribe {} => exec_unsubscribe(deps, info),
+        ExecuteMsg::EvictSubscriber { subscriber } => exec_evict_subscriber(deps, env, info, subscriber),
+        ExecuteMsg::AllowSubscriber { subscriber } => exec_allow_subscriber(deps, info, subscriber),
+
+        // Swap migration from legacy CW20s
+        ExecuteMsg::Receive(msg) => exec_receive(deps, env, info, msg),
+
         // Inherited CW20-base functions
-        ExecuteMsg::Transfer { recipient, amount } => {
-            let submsgs = before_transfer(deps.storage, deps.api, &info.sender, &recipient, amount)?;
-            Ok(execute_transfer(deps, env, info, recipient, amount)?.add_submessages(submsgs))
+        ExecuteMsg::Transfer {
+            recipient,
+            amount,
+            memo,
+        } => {
+            let ctx = before_transfer(
+                deps.storage,
+                deps.api,
+                &info.sender,
+                &recipient,
+                &memo,
+                BalanceChangeOperation::Transfer,
+            )?;
+            let resp = execute_transfer(deps.branch(), env.to_owned(), info, recipient, amount)?;
+            let submsgs = after_transfer(deps.storage, &env, ctx, amount, memo)?;
+            Ok(resp.add_submessages(submsgs))
         },
-        ExecuteMsg::Send { contract, amount, msg } => {
-            let submsgs = before_transfer(deps.storage, deps.api, &info.sender, &contract, amount)?;
-            Ok(execute_send(deps, env, info, contract, amount, msg)?.add_submessages(submsgs))
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+            memo,
+        } => {
+            let ctx = before_transfer(
+                deps.storage,
+                deps.api,
+                &info.sender,
+                &contract,
+                &memo,
+                BalanceChangeOperation::Send,
+            )?;
+            let resp = execute_send(deps.branch(), env.to_owned(), info, contract, amount, msg)?;
+            let submsgs = after_transfer(deps.storage, &env, ctx, amount, memo)?;
+            Ok(resp.add_submessages(submsgs))
         },
         ExecuteMsg::TransferFrom {
             owner,
             recipient,
             amount,
+            memo,
         } => {
-            let submsgs = before_transfer(
+            let ctx = before_transfer(
                 deps.storage,
                 deps.api,
                 &deps.api.addr_validate(&owner)?,
                 &recipient,
-                amount,
+                &memo,
+                BalanceChangeOperation::TransferFrom {
+                    spender: info.sender.to_owned(),
+                },
             )?;
-            Ok(execute_transfer_from(deps, env, info, owner, recipient, amount)?.add_submessages(submsgs))
+            let resp = execute_transfer_from(deps.branch(), env.to_owned(), info, owner, recipient, amount)?;
+            let submsgs = after_transfer(deps.storage, &env, ctx, amount, memo)?;
+            Ok(resp.add_submessages(submsgs))
         },
         ExecuteMsg::SendFrom {
             owner,
             contract,
             amount,
             msg,
+            memo,
         } => {
-            let submsgs = before_transfer(
+            let ctx = before_transfer(
                 deps.storage,
                 deps.api,
                 &deps.api.addr_validate(&owner)?,
                 &contract,
-                amount,
+                &memo,
+                BalanceChangeOperation::SendFrom {
+                    spender: info.sender.to_owned(),
+                },
             )?;
-            Ok(execute_send_from(deps, env, info, owner, contract, amount, msg)?.add_submessages(submsgs))
+            let resp = execute_send_from(deps.branch(), env.to_owned(), info, owner, contract, amount, msg)?;
+            let submsgs = after_transfer(deps.storage, &env, ctx, amount, memo)?;
+            Ok(resp.add_submessages(submsgs))
         },
         ExecuteMsg::Mint { amount, recipient } => {
-            let submsgs = before_mint(deps.storage, deps.api, &info.sender, &recipient, amount)?;
-            Ok(execute_mint(deps, env, info, recipient, amount)?.add_submessages(submsgs))
+            let ctx = before_mint(deps.storage, deps.api, &recipient)?;
+            let minter = info.sender.to_owned();
+            let resp = execute_mint(deps.branch(), env.to_owned(), info, recipient, amount)?;
+            let submsgs = after_mint(deps.storage, &env, ctx, &minter, amount)?;
+            Ok(resp.add_submessages(submsgs))
         },
         ExecuteMsg::UpdateMinter { new_minter } => Ok(execute_update_minter(deps, env, info, new_minter)?),
         ExecuteMsg::Burn { amount } => {
-            let sender = info.sender.to_string();
-            let submsgs = before_burn(deps.storage, deps.api, &sender, amount)?;
-            Ok(execute_burn(deps, env, info, amount)?.add_submessages(submsgs))
+            let ctx = before_burn(deps.storage, deps.api, info.sender.as_str(), BalanceChangeOperation::Burn)?;
+            let resp = execute_burn(deps.branch(), env.to_owned(), info, amount)?;
+            let submsgs = after_burn(deps.storage, &env, ctx, amount)?;
+            Ok(resp.add_submessages(submsgs))
         },
         ExecuteMsg::BurnFrom { owner, amount } => {
-            let submsgs = before_burn(deps.storage, deps.api, &owner, amount)?;
-            Ok(execute_burn_from(deps, env, info, owner, amount)?.add_submessages(submsgs))
+            let ctx = before_burn(
+                deps.storage,
+                deps.api,
+                &owner,
+                BalanceChangeOperation::BurnFrom {
+                    spender: info.sender.to_owned(),
+                },
+            )?;
+            let resp = execute_burn_from(deps.branch(), env.to_owned(), info, owner, amount)?;
+            let submsgs = after_burn(deps.storage, &env, ctx, amount)?;
+            Ok(resp.add_submessages(submsgs))
         },
         ExecuteMsg::IncreaseAllowance {
             spender,
             amount,
             expires,
-        } => Ok(execute_increase_allowance(deps, env, info, spender, amount, expires)?),
+        } => {
+            let resp = execute_increase_allowance(
+                deps.branch(),
+                env.to_owned(),
+                info.to_owned(),
+                spender.to_owned(),
+                amount,
+                expires,
+            )?;
+            let submsgs = after_update_allowance(deps.storage, deps.api, &env, &info.sender, &spender, amount, true)?;
+            Ok(resp.add_submessages(submsgs))
+        },
         ExecuteMsg::DecreaseAllowance {
             spender,
             amount,
             expires,
-        } => Ok(execute_decrease_allowance(deps, env, info, spender, amount, expires)?),
+        } => {
+            let resp = execute_decrease_allowance(
+                deps.branch(),
+                env.to_owned(),
+                info.to_owned(),
+                spender.to_owned(),
+                amount,
+                expires,
+            )?;
+            let submsgs = after_update_allowance(deps.storage, deps.api, &env, &info.sender, &spender, amount, false)?;
+            Ok(resp.add_submessages(submsgs))
+        },
         ExecuteMsg::UpateMarketing {
             project,
             description,
@@ -178,19 +324,25 @@ pub fn execute(
     }
 }
 
-// #[entry_point]
-// pub fn reply(
-//     deps: DepsMut,
-//     _env: Env,
-//     reply: Reply,
-// ) -> Result<Response, ContractError> {
-//     Ok(Response::new())
-// }
+#[entry_point]
+pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    reply: Reply,
+) -> Result<Response, ContractError> {
+    if reply.id >= LISTENER_REPLY_ID_OFFSET {
+        return reply_listener(deps, env, reply);
+    }
+    Err(ContractError::Std(StdError::generic_err(format!(
+        "unexpected reply ID {}",
+        reply.id
+    ))))
+}
 
 #[entry_point]
 pub fn query(
     deps: Deps,
-    _env: Env,
+    env: Env,
     msg: QueryMsg,
 ) -> Result<Binary, ContractError> {
     let result = match msg {
@@ -198,13 +350,74 @@ pub fn query(
         QueryMsg::Pro(msg) => match msg {
             // Balance-related queries
             ProQueryMsg::Balances(msg) => match msg {
-                ProBalanceQueryMsg::All { limit, desc, cursor } => {
-                    to_json_binary(&query_paginate_balances(deps, limit, desc, cursor)?)
-                },
+                ProBalanceQueryMsg::All {
+                    limit,
+                    desc,
+                    cursor,
+                    min_amount,
+                    max_amount,
+                    exclude,
+                    include_excluded,
+                } => to_json_binary(&query_paginate_balances(
+                    deps,
+                    limit,
+                    desc,
+                    cursor,
+                    min_amount,
+                    max_amount,
+                    exclude,
+                    include_excluded,
+                )?),
                 ProBalanceQueryMsg::ByAddress { addresses } => {
                     to_json_binary(&query_balances_by_address(deps, addresses)?)
                 },
             },
+            ProQueryMsg::CirculatingSupply {} => to_json_binary(&query_circulating_supply(deps)?),
+            ProQueryMsg::HolderCount {} => to_json_binary(&query_holder_count(deps)?),
+            ProQueryMsg::ExcludedAccounts { limit, cursor } => {
+                to_json_binary(&query_excluded_accounts(deps, limit, cursor)?)
+            },
+            ProQueryMsg::LegacyTokens {} => to_json_binary(&query_legacy_tokens(deps)?),
+            ProQueryMsg::Tiers { include_excluded } => to_json_binary(&query_tiers(deps, include_excluded)?),
+            ProQueryMsg::Tier { address } => to_json_binary(&query_account_tier(deps, address)?),
+            ProQueryMsg::TierHolders {
+                tier,
+                limit,
+                desc,
+                cursor,
+                include_excluded,
+            } => to_json_binary(&query_tier_holders(
+                deps,
+                tier,
+                limit,
+                desc,
+                cursor,
+                include_excluded,
+            )?),
+            ProQueryMsg::Stats { include_excluded } => to_json_binary(&query_stats(deps, include_excluded)?),
+            ProQueryMsg::History { from, to, limit } => to_json_binary(&query_history(deps, from, to, limit)?),
+            ProQueryMsg::AccountStats { address } => to_json_binary(&query_account_stats(deps, address)?),
+            ProQueryMsg::TopSenders { limit, cursor } => to_json_binary(&query_top_senders(deps, limit, cursor)?),
+            ProQueryMsg::TransferHistory { address, limit, cursor } => {
+                to_json_binary(&query_transfer_history(deps, address, limit, cursor)?)
+            },
+            ProQueryMsg::ListenerHealth {} => to_json_binary(&query_listener_health(deps)?),
+            ProQueryMsg::Listeners { limit, cursor } => to_json_binary(&query_listeners(deps, limit, cursor)?),
+            ProQueryMsg::ListenerQueue {
+                listener,
+                limit,
+                cursor,
+            } => to_json_binary(&query_listener_queue(deps, listener, limit, cursor)?),
+            ProQueryMsg::Subscription { subscriber } => to_json_binary(&query_subscription(deps, subscriber)?),
+            ProQueryMsg::Audit { limit, cursor } => to_json_binary(&query_audit(deps, limit, cursor)?),
+            ProQueryMsg::Export { limit, cursor } => to_json_binary(&query_export(deps, limit, cursor)?),
+            ProQueryMsg::CopyJob {} => to_json_binary(&query_copy_job(deps)?),
+            ProQueryMsg::CopyPreview {
+                source,
+                mode,
+                cap_behavior,
+                remap_prefix,
+            } => to_json_binary(&query_copy_preview(deps, env, source, mode, cap_behavior, remap_prefix)?),
         },
 
         // inherited from cw20-base
@@ -232,9 +445,8 @@ pub fn query(
 #[entry_point]
 pub fn migrate(
     deps: DepsMut,
-    _env: Env,
-    _msg: MigrateMsg,
+    env: Env,
+    msg: MigrateMsg,
 ) -> Result<Response, ContractError> {
-    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::default())
+    exec_migrate(deps, env, msg)
 }
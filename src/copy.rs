@@ -0,0 +1,437 @@
+use std::str::FromStr;
+
+use bech32::{FromBase32, ToBase32};
+use cosmwasm_std::{from_json, to_json_string, Addr, Api, Binary, Deps, Env, StdError, StdResult, Storage, Uint128};
+use cw20::{AllAccountsResponse, BalanceResponse, Cw20QueryMsg};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+use crate::{
+    error::ContractError,
+    math::{add_u128, div_u128, mul_u128, sub_u128},
+    msg::{
+        AccountBalance, BalanceCopyJob, BalanceCopyMode, BalancesResponse, CopyBalanceChange, CopyCapBehavior,
+        CopySource, CopySourceKind, ProBalanceQueryMsg, ProQueryMsg, QueryMsg,
+    },
+    state::{BALANCE_COPY_CURSORS, BALANCE_COPY_JOB},
+    tf::cosmos::bank::{PageRequest, QueryDenomOwnersRequest, QueryDenomOwnersResponse},
+};
+
+const ALL_BALANCES_QUERY_LIMIT: u32 = 30;
+const NUM_BALANCES_QUERY_PER_EXECUTION: u32 = 2;
+const LIMIT: u64 = 50;
+/// Page size when copying from another cw20-pro, its max balances query limit
+const PRO_BALANCES_QUERY_LIMIT: u16 = 500;
+/// Page size when copying the holders of a bank denom
+const DENOM_OWNERS_QUERY_LIMIT: u64 = 100;
+/// Length of account addresses that can be moved to another bech32 prefix
+const REMAPPABLE_ADDR_LEN: usize = 20;
+
+/// Balance changes that the next CopyBalances call will apply
+pub struct CopyBatch {
+    pub changes: Vec<CopyBalanceChange>,
+    /// Total supply once the changes are applied
+    pub total_supply: Uint128,
+    /// Source addresses left out because they couldn't be remapped
+    pub skipped: Vec<String>,
+    /// Source cursor to resume from, or None if the copy is done
+    pub cursor: Option<String>,
+}
+
+/// Return total supply adjusted by the net change of a copied balance
+pub fn apply_supply_delta(
+    total_supply: Uint128,
+    change: &CopyBalanceChange,
+) -> Result<Uint128, ContractError> {
+    if change.next_balance >= change.prev_balance {
+        add_u128(total_supply, change.next_balance - change.prev_balance)
+    } else {
+        sub_u128(total_supply, change.prev_balance - change.next_balance)
+    }
+}
+
+/// Load the copy in progress, checking that the given settings agree with the
+/// ones it was started with, or set up a new job. Settings left out default
+/// to the job's, so that CopyBalances and its preview plan the same batch.
+pub fn load_copy_job(
+    store: &dyn Storage,
+    source: &CopySource,
+    source_kind: &CopySourceKind,
+    copy_mode: &BalanceCopyMode,
+    cap_behavior: Option<CopyCapBehavior>,
+    remap_prefix: Option<bool>,
+) -> Result<BalanceCopyJob, ContractError> {
+    let job = match BALANCE_COPY_JOB.may_load(store)? {
+        Some(job) => job,
+        None => {
+            return Ok(BalanceCopyJob {
+                source: source.to_owned(),
+                source_kind: source_kind.to_owned(),
+                mode: copy_mode.to_owned(),
+                cap_behavior: cap_behavior.unwrap_or_default(),
+                remap_prefix: remap_prefix.unwrap_or(false),
+                n_accounts: 0,
+                n_skipped: 0,
+                amount_added: Uint128::zero(),
+                amount_removed: Uint128::zero(),
+            })
+        },
+    };
+    if job.mode != *copy_mode {
+        return Err(ContractError::ValidationError {
+            reason: format!("copy from {} was started in {:?} mode", job.source.key(), job.mode),
+        });
+    }
+    if remap_prefix.is_some_and(|remap_prefix| remap_prefix != job.remap_prefix) {
+        return Err(ContractError::ValidationError {
+            reason: format!(
+                "copy from {} was started with remap_prefix {}",
+                job.source.key(),
+                job.remap_prefix
+            ),
+        });
+    }
+    if cap_behavior.is_some_and(|cap_behavior| cap_behavior != job.cap_behavior) {
+        return Err(ContractError::ValidationError {
+            reason: format!(
+                "copy from {} was started with cap behavior {:?}",
+                job.source.key(),
+                job.cap_behavior
+            ),
+        });
+    }
+    Ok(job)
+}
+
+/// Determine how to read balances from the copy source. A job in progress
+/// keeps the kind it started with, and a cursor left by a copy from before jobs
+/// were tracked implies a plain CW20. Otherwise, the source is probed with a
+/// cw20-pro balances query, and treated as a plain CW20 only if it doesn't
+/// recognize it.
+pub fn load_copy_source_kind(
+    deps: Deps,
+    source: &CopySource,
+) -> Result<CopySourceKind, ContractError> {
+    let other_cw20_addr = match source {
+        CopySource::Cw20 { address } => address,
+        CopySource::Denom { .. } => return Ok(CopySourceKind::Denom),
+    };
+    if let Some(job) = BALANCE_COPY_JOB.may_load(deps.storage)? {
+        if job.source == *source {
+            return Ok(job.source_kind);
+        }
+    }
+    if BALANCE_COPY_CURSORS.has(deps.storage, other_cw20_addr.as_str()) {
+        return Ok(CopySourceKind::Cw20);
+    }
+    let probe: StdResult<BalancesResponse> = deps
+        .querier
+        .query_wasm_smart(other_cw20_addr.to_owned(), &pro_balances_query(None, 1, false));
+    match probe {
+        Ok(_) => Ok(CopySourceKind::Cw20Pro),
+        // Only a source that doesn't understand the query is a plain CW20.
+        // Other errors, like a cw20-pro source in the middle of a Reindex,
+        // would otherwise silently switch the copy to the slower path.
+        Err(e) if e.to_string().contains("unknown variant") => Ok(CopySourceKind::Cw20),
+        Err(e) => Err(ContractError::ValidationError {
+            reason: format!("failed to read balances from {}: {}", other_cw20_addr, e),
+        }),
+    }
+}
+
+/// Balances query for another cw20-pro, in descending order of amount, only
+/// including the fields that every version of it understands unless
+/// include_excluded is set
+fn pro_balances_query(
+    cursor: Option<(Uint128, Addr)>,
+    limit: u16,
+    include_excluded: bool,
+) -> QueryMsg {
+    QueryMsg::Pro(ProQueryMsg::Balances(ProBalanceQueryMsg::All {
+        limit: Some(limit),
+        desc: Some(true),
+        cursor,
+        min_amount: None,
+        max_amount: None,
+        exclude: None,
+        include_excluded: include_excluded.then_some(true),
+    }))
+}
+
+/// Work out the next batch of balances to copy from the source without
+/// writing anything, so that it can back both CopyBalances and its preview.
+pub fn plan_copy_batch(
+    deps: Deps,
+    source: &CopySource,
+    source_kind: &CopySourceKind,
+    copy_mode: &BalanceCopyMode,
+    cap_behavior: &CopyCapBehavior,
+    remap_prefix: Option<&str>,
+) -> Result<CopyBatch, ContractError> {
+    let source_key = source.key();
+    let cursor: Option<String> = BALANCE_COPY_CURSORS.may_load(deps.storage, source_key.as_str())?;
+
+    // Fail if we're already in the middle of copying some other CW20. Just one
+    // at a time, baby...
+    if !BALANCE_COPY_CURSORS.is_empty(deps.storage) && !BALANCE_COPY_CURSORS.has(deps.storage, source_key.as_str()) {
+        return Err(ContractError::Unauthorized {
+            reason: format!(
+                "copy already in progress for {}",
+                BALANCE_COPY_CURSORS.first(deps.storage)?.unwrap().0
+            ),
+        });
+    }
+
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+
+    let (source_balances, cursor) = match (source, source_kind) {
+        (CopySource::Denom { denom, exponent }, _) => {
+            fetch_denom_balances(deps, denom, *exponent, token_info.decimals, cursor)?
+        },
+        (CopySource::Cw20 { address }, CopySourceKind::Cw20Pro) => fetch_cw20_pro_balances(deps, address, cursor)?,
+        (CopySource::Cw20 { address }, _) => fetch_cw20_balances(deps, address, cursor)?,
+    };
+
+    let cap = token_info.get_cap();
+    let mut total_supply = token_info.total_supply;
+    let mut changes: Vec<CopyBalanceChange> = Vec::with_capacity(source_balances.len());
+    let mut skipped: Vec<String> = vec![];
+
+    for AccountBalance {
+        address,
+        amount: balance,
+    } in source_balances
+    {
+        let address = match remap_prefix {
+            Some(prefix) => match remap_address(deps.api, address.as_str(), prefix) {
+                Some(address) => address,
+                None => {
+                    skipped.push(address.into_string());
+                    continue;
+                },
+            },
+            None => address,
+        };
+
+        // Overwrite or increment existing balances based on copy mode
+        let prev_balance = BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+        let mut change = CopyBalanceChange {
+            next_balance: match copy_mode {
+                BalanceCopyMode::Replace => balance,
+                BalanceCopyMode::Increment => add_u128(prev_balance, balance)?,
+            },
+            address,
+            prev_balance,
+        };
+
+        // Keep total supply within the minter cap
+        let mut next_supply = apply_supply_delta(total_supply, &change)?;
+        if let Some(cap) = cap {
+            if next_supply > cap && change.next_balance > prev_balance {
+                match cap_behavior {
+                    CopyCapBehavior::Fail => return Err(ContractError::CannotExceedCap {}),
+                    CopyCapBehavior::Truncate => {
+                        change.next_balance = add_u128(prev_balance, cap.saturating_sub(total_supply))?;
+                        next_supply = apply_supply_delta(total_supply, &change)?;
+                    },
+                }
+            }
+        }
+        total_supply = next_supply;
+        changes.push(change);
+    }
+
+    Ok(CopyBatch {
+        changes,
+        total_supply,
+        skipped,
+        cursor,
+    })
+}
+
+/// Bech32 prefix of this chain's addresses, taken from the contract's own
+pub fn local_bech32_prefix(env: &Env) -> Result<String, ContractError> {
+    match bech32::decode(env.contract.address.as_str()) {
+        Ok((prefix, _, _)) => Ok(prefix),
+        Err(e) => Err(ContractError::ValidationError {
+            reason: format!("cannot read bech32 prefix of contract address: {}", e),
+        }),
+    }
+}
+
+/// Re-encode an account address from another chain with the given prefix.
+/// Returns None for addresses that aren't bech32, aren't 20-byte accounts, like
+/// contract addresses, or aren't valid here once converted.
+fn remap_address(
+    api: &dyn Api,
+    address: &str,
+    prefix: &str,
+) -> Option<Addr> {
+    let (_, data, variant) = bech32::decode(address).ok()?;
+    let bytes = Vec::<u8>::from_base32(&data).ok()?;
+    if bytes.len() != REMAPPABLE_ADDR_LEN {
+        return None;
+    }
+    let remapped = bech32::encode(prefix, bytes.to_base32(), variant).ok()?;
+    api.addr_validate(&remapped).ok()
+}
+
+/// Read the next balances from a plain CW20, one Balance query per account
+/// listed by AllAccounts. Returns the balances along with the cursor to resume
+/// from, or None once the source has no more accounts.
+fn fetch_cw20_balances(
+    deps: Deps,
+    other_cw20_addr: &Addr,
+    mut cursor: Option<String>,
+) -> Result<(Vec<AccountBalance>, Option<String>), ContractError> {
+    let mut balances: Vec<AccountBalance> = vec![];
+    let mut t = 0;
+
+    for _ in 0..NUM_BALANCES_QUERY_PER_EXECUTION {
+        // fetch next batch of cw20 account address
+        let AllAccountsResponse { accounts } = deps.querier.query_wasm_smart(
+            deps.api.addr_validate(other_cw20_addr.as_str())?,
+            &Cw20QueryMsg::AllAccounts {
+                start_after: cursor.to_owned(),
+                limit: Some(ALL_BALANCES_QUERY_LIMIT),
+            },
+        )?;
+
+        // iterate over the cw20's balances in batches of 30, since this is the
+        // max limit on its all_accounts query :(
+        for address in accounts.iter() {
+            let BalanceResponse { balance } = deps.querier.query_wasm_smart(
+                other_cw20_addr.to_owned(),
+                &Cw20QueryMsg::Balance {
+                    address: address.to_owned(),
+                },
+            )?;
+
+            cursor = Some(address.to_owned());
+            balances.push(AccountBalance {
+                address: Addr::unchecked(address),
+                amount: balance,
+            });
+
+            t += 1;
+            if t == LIMIT {
+                break;
+            }
+        }
+
+        if t == LIMIT {
+            break;
+        }
+
+        // Stop once the source has no more accounts
+        if accounts.len() < ALL_BALANCES_QUERY_LIMIT as usize {
+            cursor = None;
+            break;
+        }
+    }
+
+    Ok((balances, cursor))
+}
+
+/// Read the next page of non-zero balances from another cw20-pro contract, in
+/// descending order of amount. The cursor is the source's JSON-encoded balance
+/// cursor. Accounts whose source balance changes between pages may be missed
+/// or copied twice, so the source should be frozen while copying.
+fn fetch_cw20_pro_balances(
+    deps: Deps,
+    other_cw20_addr: &Addr,
+    cursor: Option<String>,
+) -> Result<(Vec<AccountBalance>, Option<String>), ContractError> {
+    let cursor: Option<(Uint128, Addr)> = cursor.map(|c| from_json(c.as_bytes())).transpose()?;
+    // Include excluded accounts, falling back to the plain query for sources
+    // that predate the excluded-account registry and reject the field
+    let BalancesResponse { balances, cursor } = deps
+        .querier
+        .query_wasm_smart(
+            other_cw20_addr.to_owned(),
+            &pro_balances_query(cursor.to_owned(), PRO_BALANCES_QUERY_LIMIT, true),
+        )
+        .or_else(|e| {
+            if !e.to_string().contains("unknown field") {
+                return Err(e);
+            }
+            deps.querier.query_wasm_smart(
+                other_cw20_addr.to_owned(),
+                &pro_balances_query(cursor, PRO_BALANCES_QUERY_LIMIT, false),
+            )
+        })?;
+    let cursor = cursor.map(|c| to_json_string(&c)).transpose()?;
+    Ok((balances, cursor))
+}
+
+/// Read the next page of holders of a bank denom with the DenomOwners query,
+/// converting amounts from the denom's exponent to this token's decimals if
+/// the exponent is given. The cursor is the base64 pagination key.
+fn fetch_denom_balances(
+    deps: Deps,
+    denom: &str,
+    exponent: Option<u8>,
+    decimals: u8,
+    cursor: Option<String>,
+) -> Result<(Vec<AccountBalance>, Option<String>), ContractError> {
+    let key = match cursor {
+        Some(cursor) => Binary::from_base64(&cursor)?.to_vec(),
+        None => vec![],
+    };
+
+    let QueryDenomOwnersResponse {
+        denom_owners,
+        pagination,
+    } = QueryDenomOwnersRequest {
+        denom: denom.to_owned(),
+        pagination: Some(PageRequest {
+            key,
+            offset: 0,
+            limit: DENOM_OWNERS_QUERY_LIMIT,
+            count_total: false,
+            reverse: false,
+        }),
+    }
+    .query(&deps.querier)?;
+
+    let mut balances: Vec<AccountBalance> = Vec::with_capacity(denom_owners.len());
+    for owner in denom_owners {
+        let amount = match owner.balance {
+            Some(coin) => Uint128::from_str(&coin.amount)?,
+            None => continue,
+        };
+        balances.push(AccountBalance {
+            address: deps.api.addr_validate(&owner.address)?,
+            amount: convert_denom_amount(amount, exponent, decimals)?,
+        });
+    }
+
+    let cursor = pagination
+        .map(|p| p.next_key)
+        .filter(|k| !k.is_empty())
+        .map(|k| Binary::from(k).to_base64());
+
+    Ok((balances, cursor))
+}
+
+/// Scale an amount of a bank denom from its exponent to the given decimals,
+/// rounding down when there are fewer decimals
+fn convert_denom_amount(
+    amount: Uint128,
+    exponent: Option<u8>,
+    decimals: u8,
+) -> Result<Uint128, ContractError> {
+    let exponent = match exponent {
+        Some(exponent) => exponent,
+        None => return Ok(amount),
+    };
+    let scale = |diff: u8| {
+        Uint128::from(10u128)
+            .checked_pow(diff as u32)
+            .map_err(|e| ContractError::Std(StdError::overflow(e)))
+    };
+    if exponent < decimals {
+        mul_u128(amount, scale(decimals - exponent)?)
+    } else {
+        div_u128(amount, scale(exponent - decimals)?)
+    }
+}
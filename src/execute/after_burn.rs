@@ -0,0 +1,44 @@
+use crate::{
+    activity::record_burn,
+    error::ContractError,
+    execute::hooks::{after_balance_changes, load_balance_changes, HookContext},
+    history::update_history,
+    listeners::notify_balance_change_listeners,
+    msg::{BalanceChangeEvent, BalanceChangeEventV2},
+};
+use cosmwasm_std::{Env, Storage, SubMsg, Uint128};
+use cw20_base::state::TOKEN_INFO;
+
+/// Custom business logic that executes AFTER the cw20 base burn function
+pub fn after_burn(
+    store: &mut dyn Storage,
+    env: &Env,
+    ctx: HookContext,
+    amount: Uint128,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let changes = load_balance_changes(store, &ctx)?;
+    let burner = &changes[0];
+    let total_supply = TOKEN_INFO.load(store)?.total_supply;
+
+    let mut submsgs = notify_balance_change_listeners(
+        store,
+        &BalanceChangeEventV2::new(
+            env,
+            BalanceChangeEvent::Burn {
+                initiator: burner.address.to_owned(),
+                initiator_balance: burner.next_balance,
+                amount,
+            },
+            ctx.operation.to_owned(),
+            ctx.prev_balances.to_owned(),
+            total_supply,
+        ),
+    )?;
+
+    submsgs.extend(after_balance_changes(store, env, &ctx, &changes, total_supply)?);
+
+    record_burn(store, &burner.address, amount)?;
+    update_history(store, env, None)?;
+
+    Ok(submsgs)
+}
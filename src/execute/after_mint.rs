@@ -0,0 +1,46 @@
+use crate::{
+    activity::record_mint,
+    error::ContractError,
+    execute::hooks::{after_balance_changes, load_balance_changes, HookContext},
+    history::update_history,
+    listeners::notify_balance_change_listeners,
+    msg::{BalanceChangeEvent, BalanceChangeEventV2},
+};
+use cosmwasm_std::{Addr, Env, Storage, SubMsg, Uint128};
+use cw20_base::state::TOKEN_INFO;
+
+/// Custom business logic that executes AFTER the cw20 base mint function
+pub fn after_mint(
+    store: &mut dyn Storage,
+    env: &Env,
+    ctx: HookContext,
+    minter: &Addr,
+    amount: Uint128,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let changes = load_balance_changes(store, &ctx)?;
+    let recipient = &changes[0];
+    let total_supply = TOKEN_INFO.load(store)?.total_supply;
+
+    let mut submsgs = notify_balance_change_listeners(
+        store,
+        &BalanceChangeEventV2::new(
+            env,
+            BalanceChangeEvent::Mint {
+                initiator: minter.to_owned(),
+                recipient: recipient.address.to_owned(),
+                recipient_balance: recipient.next_balance,
+                amount,
+            },
+            ctx.operation.to_owned(),
+            ctx.prev_balances.to_owned(),
+            total_supply,
+        ),
+    )?;
+
+    submsgs.extend(after_balance_changes(store, env, &ctx, &changes, total_supply)?);
+
+    record_mint(store, &recipient.address, amount)?;
+    update_history(store, env, None)?;
+
+    Ok(submsgs)
+}
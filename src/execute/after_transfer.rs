@@ -0,0 +1,50 @@
+use crate::{
+    activity::record_transfer,
+    error::ContractError,
+    execute::hooks::{after_balance_changes, load_balance_changes, HookContext},
+    history::update_history,
+    listeners::notify_balance_change_listeners,
+    msg::{BalanceChangeEvent, BalanceChangeEventV2},
+    transfer_history::record_transfer_history,
+};
+use cosmwasm_std::{Env, Storage, SubMsg, Uint128};
+use cw20_base::state::TOKEN_INFO;
+
+/// Custom business logic that fires after the cw20-base send and transfer
+/// functions have updated balances.
+pub fn after_transfer(
+    store: &mut dyn Storage,
+    env: &Env,
+    ctx: HookContext,
+    amount: Uint128,
+    memo: Option<String>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let changes = load_balance_changes(store, &ctx)?;
+    let (sender, recipient) = (&changes[0], &changes[1]);
+    let total_supply = TOKEN_INFO.load(store)?.total_supply;
+
+    let mut submsgs = notify_balance_change_listeners(
+        store,
+        &BalanceChangeEventV2::new(
+            env,
+            BalanceChangeEvent::Transfer {
+                initiator: sender.address.to_owned(),
+                initiator_balance: sender.next_balance,
+                recipient: recipient.address.to_owned(),
+                recipient_balance: recipient.next_balance,
+                amount,
+            },
+            ctx.operation.to_owned(),
+            ctx.prev_balances.to_owned(),
+            total_supply,
+        ),
+    )?;
+
+    submsgs.extend(after_balance_changes(store, env, &ctx, &changes, total_supply)?);
+
+    record_transfer(store, &sender.address, &recipient.address, amount)?;
+    record_transfer_history(store, env, &sender.address, &recipient.address, amount, &memo)?;
+    update_history(store, env, Some(amount))?;
+
+    Ok(submsgs)
+}
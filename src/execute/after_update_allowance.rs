@@ -0,0 +1,56 @@
+use crate::{
+    error::ContractError,
+    listeners::notify_balance_change_listeners,
+    msg::{BalanceChangeEvent, BalanceChangeEventV2, BalanceChangeOperation},
+};
+use cosmwasm_std::{Addr, Api, Env, Storage, SubMsg, Uint128};
+use cw20_base::state::{ALLOWANCES, TOKEN_INFO};
+
+/// Custom business logic that executes AFTER the cw20 base increase and
+/// decrease allowance functions, notifying listeners of the new allowance.
+pub fn after_update_allowance(
+    store: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    owner: &Addr,
+    spender: &str,
+    amount: Uint128,
+    increased: bool,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let spender = api.addr_validate(spender)?;
+
+    // Allowances decreased to zero are removed by cw20-base
+    let allowance = ALLOWANCES
+        .may_load(store, (owner, &spender))?
+        .map(|a| a.allowance)
+        .unwrap_or_default();
+
+    let (event, operation) = if increased {
+        (
+            BalanceChangeEvent::AllowanceIncreased {
+                owner: owner.to_owned(),
+                spender,
+                amount,
+                allowance,
+            },
+            BalanceChangeOperation::IncreaseAllowance,
+        )
+    } else {
+        (
+            BalanceChangeEvent::AllowanceDecreased {
+                owner: owner.to_owned(),
+                spender,
+                amount,
+                allowance,
+            },
+            BalanceChangeOperation::DecreaseAllowance,
+        )
+    };
+
+    let total_supply = TOKEN_INFO.load(store)?.total_supply;
+
+    notify_balance_change_listeners(
+        store,
+        &BalanceChangeEventV2::new(env, event, operation, vec![], total_supply),
+    )
+}
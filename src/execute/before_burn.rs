@@ -1,55 +1,18 @@
 use crate::{
-    checks::ensure_accounts_not_frozen,
-    error::ContractError,
-    math::{sub_u128, sub_u64},
-    msg::BalanceChangeEvent,
-    state::{N_BALANCES, ORDERED_BALANCES},
+    checks::ensure_accounts_not_frozen, error::ContractError, execute::hooks::HookContext, msg::BalanceChangeOperation,
 };
-use cosmwasm_std::{Addr, Api, Storage, SubMsg, Uint128};
-use cw20_base::state::BALANCES;
-
-use super::before_transfer::notify_balance_change_listeners;
+use cosmwasm_std::{Api, Storage};
 
 /// Custom business logic that executes BEFORE the cw20 base burn function
 pub fn before_burn(
-    store: &mut dyn Storage,
+    store: &dyn Storage,
     api: &dyn Api,
-    burner: &String,
-    delta: Uint128,
-) -> Result<Vec<SubMsg>, ContractError> {
-    let burner = api.addr_validate(&burner)?;
+    burner: &str,
+    operation: BalanceChangeOperation,
+) -> Result<HookContext, ContractError> {
+    let burner = api.addr_validate(burner)?;
 
     ensure_accounts_not_frozen(store, Some(burner.to_owned()), None)?;
 
-    let initiator_balance = update_ordered_balance(store, &burner, delta)?;
-
-    let submsgs = notify_balance_change_listeners(
-        store,
-        &BalanceChangeEvent::Burn {
-            initiator: burner.to_owned(),
-            initiator_balance,
-            amount: delta,
-        },
-    )?;
-
-    Ok(submsgs)
-}
-
-/// Update the account's entry in the RANKED_BALANCES map and decrement the
-/// aggregate balance counter if necessary.
-fn update_ordered_balance(
-    store: &mut dyn Storage,
-    burner: &Addr,
-    delta: Uint128,
-) -> Result<Uint128, ContractError> {
-    let prev_balance: Uint128 = BALANCES.load(store, burner).unwrap_or_default();
-    let next_balance = sub_u128(prev_balance, delta)?;
-
-    ORDERED_BALANCES.remove(store, (prev_balance.u128(), burner));
-    if !next_balance.is_zero() {
-        ORDERED_BALANCES.save(store, (next_balance.u128(), burner), &0)?;
-        N_BALANCES.update(store, |n| sub_u64(n, 1u64))?;
-    }
-
-    Ok(next_balance)
+    HookContext::new(store, operation, &[&burner])
 }
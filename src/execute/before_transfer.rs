@@ -1,99 +1,27 @@
 use crate::{
-    checks::{ensure_accounts_not_frozen, ensure_not_self_transfer},
+    checks::{ensure_accounts_not_frozen, ensure_not_self_transfer, ensure_valid_memo},
     error::ContractError,
-    math::{add_u128, add_u64, sub_u128, sub_u64},
-    msg::{BalanceChangeEvent, BalanceChangeListenerInterface},
-    state::{BALANCE_CHANGE_LISTENERS, N_BALANCES, ORDERED_BALANCES},
+    execute::hooks::HookContext,
+    msg::BalanceChangeOperation,
 };
-use cosmwasm_std::{to_json_binary, Addr, Api, Storage, SubMsg, Uint128, WasmMsg};
-use cw20_base::state::BALANCES;
+use cosmwasm_std::{Addr, Api, Storage};
 
 /// Custom business logic that fires before the cw20-base send and transfer
-/// functions.
+/// functions. Validates the transfer and captures the sender and recipient
+/// balances, in that order, for the after hook.
 pub fn before_transfer(
-    store: &mut dyn Storage,
+    store: &dyn Storage,
     api: &dyn Api,
     sender: &Addr,
-    recipient: &String,
-    delta: Uint128,
-) -> Result<Vec<SubMsg>, ContractError> {
-    let recipient = api.addr_validate(&recipient)?;
+    recipient: &str,
+    memo: &Option<String>,
+    operation: BalanceChangeOperation,
+) -> Result<HookContext, ContractError> {
+    let recipient = api.addr_validate(recipient)?;
 
     ensure_not_self_transfer(sender, &recipient)?;
+    ensure_valid_memo(memo)?;
     ensure_accounts_not_frozen(store, Some(sender.to_owned()), Some(recipient.to_owned()))?;
 
-    let (initiator_balance, recipient_balance) = update_ordered_balances(store, sender, &recipient, delta)?;
-
-    let submsgs = notify_balance_change_listeners(
-        store,
-        &BalanceChangeEvent::Transfer {
-            initiator: sender.to_owned(),
-            initiator_balance,
-            recipient: recipient.to_owned(),
-            recipient_balance,
-            amount: delta,
-        },
-    )?;
-
-    Ok(submsgs)
-}
-
-/// Update index for paginating accounts by balance amounts. Return new balances
-/// for sender and recipient.
-pub fn update_ordered_balances(
-    store: &mut dyn Storage,
-    sender: &Addr,
-    recipient: &Addr,
-    delta: Uint128,
-) -> Result<(Uint128, Uint128), ContractError> {
-    // Adjust senders's entry in ordered balances map
-    let new_sender_balance = {
-        let prev_balance = BALANCES.load(store, &sender).unwrap_or_default();
-        let next_balance = sub_u128(prev_balance, delta)?;
-
-        ORDERED_BALANCES.remove(store, (prev_balance.u128(), &sender));
-        if !next_balance.is_zero() {
-            ORDERED_BALANCES.save(store, (next_balance.u128(), &sender), &0)?;
-        } else {
-            N_BALANCES.update(store, |n| sub_u64(n, 1u64))?;
-        }
-        next_balance
-    };
-
-    // Adjust recipient's entry in ordered balances map
-    let new_recipient_balance = {
-        let prev_balance = BALANCES.load(store, &recipient).unwrap_or_default();
-        let next_balance = add_u128(prev_balance, delta)?;
-
-        ORDERED_BALANCES.remove(store, (prev_balance.u128(), &recipient));
-        if !next_balance.is_zero() {
-            ORDERED_BALANCES.save(store, (next_balance.u128(), &recipient), &0)?;
-            if prev_balance.is_zero() {
-                N_BALANCES.update(store, |n| add_u64(n, 1u64))?;
-            }
-        }
-        next_balance
-    };
-
-    Ok((new_sender_balance, new_recipient_balance))
-}
-
-// Execute callback on balance change listener contracts
-pub fn notify_balance_change_listeners(
-    store: &dyn Storage,
-    event: &BalanceChangeEvent,
-) -> Result<Vec<SubMsg>, ContractError> {
-    let mut submsgs = Vec::with_capacity(1);
-    if let Some(listener_addrs) = BALANCE_CHANGE_LISTENERS.may_load(store)? {
-        for contract_addr in listener_addrs.iter() {
-            submsgs.push(SubMsg::new(WasmMsg::Execute {
-                contract_addr: contract_addr.to_string(),
-                funds: vec![],
-                msg: to_json_binary(&BalanceChangeListenerInterface::OnBalanceChange {
-                    event: event.to_owned(),
-                })?,
-            }))
-        }
-    }
-    Ok(submsgs)
+    HookContext::new(store, operation, &[sender, &recipient])
 }
@@ -0,0 +1,179 @@
+use crate::{
+    error::ContractError,
+    listeners::notify_balance_change_listeners,
+    math::{add_u64, sub_u64},
+    msg::{
+        AccountBalance, BalanceChangeEvent, BalanceChangeEventV2, BalanceChangeOperation, ReindexPhase, ReindexState,
+    },
+    state::{N_BALANCES, ORDERED_BALANCES, REINDEX_STATE},
+    stats::update_balance_stats,
+    tiers::update_holder_tier,
+};
+use cosmwasm_std::{Addr, Env, Storage, SubMsg, Uint128};
+use cw20_base::state::BALANCES;
+
+/// State captured by a before hook and handed to the matching after hook once
+/// cw20-base has applied the operation.
+pub struct HookContext {
+    pub operation: BalanceChangeOperation,
+    /// Balances of the accounts touched by the operation, prior to applying it,
+    /// in the order the before hook listed them.
+    pub prev_balances: Vec<AccountBalance>,
+}
+
+impl HookContext {
+    pub fn new(
+        store: &dyn Storage,
+        operation: BalanceChangeOperation,
+        accounts: &[&Addr],
+    ) -> Result<Self, ContractError> {
+        let mut prev_balances = Vec::with_capacity(accounts.len());
+        for address in accounts.iter() {
+            prev_balances.push(AccountBalance {
+                address: (*address).to_owned(),
+                amount: BALANCES.may_load(store, address)?.unwrap_or_default(),
+            });
+        }
+        Ok(Self {
+            operation,
+            prev_balances,
+        })
+    }
+}
+
+/// An account's balance before and after an operation
+pub struct BalanceChange {
+    pub address: Addr,
+    pub prev_balance: Uint128,
+    pub next_balance: Uint128,
+}
+
+/// Pair each account's balance from before the operation with its committed
+/// balance, in the same order as the context's prev_balances.
+pub fn load_balance_changes(
+    store: &dyn Storage,
+    ctx: &HookContext,
+) -> Result<Vec<BalanceChange>, ContractError> {
+    let mut changes = Vec::with_capacity(ctx.prev_balances.len());
+    for prev in ctx.prev_balances.iter() {
+        changes.push(BalanceChange {
+            address: prev.address.to_owned(),
+            prev_balance: prev.amount,
+            next_balance: BALANCES.may_load(store, &prev.address)?.unwrap_or_default(),
+        });
+    }
+    Ok(changes)
+}
+
+/// Bring the ordered balance index, holder count, distribution stats and tier
+/// counts in line with the committed balances, notifying listeners of any
+/// resulting holder-set and tier changes.
+pub fn after_balance_changes(
+    store: &mut dyn Storage,
+    env: &Env,
+    ctx: &HookContext,
+    changes: &[BalanceChange],
+    total_supply: Uint128,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let mut submsgs: Vec<SubMsg> = vec![];
+    for change in changes.iter() {
+        for event in sync_balance_change(store, change)? {
+            submsgs.extend(notify_balance_change_listeners(
+                store,
+                &BalanceChangeEventV2::new(
+                    env,
+                    event,
+                    ctx.operation.to_owned(),
+                    ctx.prev_balances.to_owned(),
+                    total_supply,
+                ),
+            )?);
+        }
+    }
+    Ok(submsgs)
+}
+
+/// Update all state derived from an account's balance after it changes,
+/// returning any holder-set and tier change events this produces. Accounts
+/// that an in-progress Reindex hasn't reached yet are skipped, since they'll be
+/// indexed with their committed balance when it does.
+pub fn sync_balance_change(
+    store: &mut dyn Storage,
+    change: &BalanceChange,
+) -> Result<Vec<BalanceChangeEvent>, ContractError> {
+    if !is_account_indexed(store, &change.address)? {
+        return Ok(vec![]);
+    }
+    index_balance_change(store, change)
+}
+
+/// Apply a balance change to the ordered balance index, holder count, stats
+/// and tier counts, regardless of any Reindex in progress.
+pub fn index_balance_change(
+    store: &mut dyn Storage,
+    change: &BalanceChange,
+) -> Result<Vec<BalanceChangeEvent>, ContractError> {
+    let mut events: Vec<BalanceChangeEvent> = Vec::with_capacity(2);
+    events.extend(update_ordered_balance(store, change)?);
+    update_balance_stats(store, change.prev_balance, change.next_balance)?;
+    events.extend(update_holder_tier(
+        store,
+        &change.address,
+        change.prev_balance,
+        change.next_balance,
+    )?);
+    Ok(events)
+}
+
+/// Whether the account's balance is currently reflected in the ordered balance
+/// index. False only while a Reindex is clearing the index or has yet to reach
+/// the account.
+fn is_account_indexed(
+    store: &dyn Storage,
+    address: &Addr,
+) -> Result<bool, ContractError> {
+    Ok(match REINDEX_STATE.may_load(store)? {
+        None => true,
+        Some(ReindexState {
+            phase: ReindexPhase::Indexing,
+            cursor: Some(cursor),
+            ..
+        }) => *address <= cursor,
+        Some(_) => false,
+    })
+}
+
+/// Move the account's entry in the ORDERED_BALANCES index and adjust the
+/// aggregate holder count when its balance crosses zero, returning a
+/// HolderAdded or HolderRemoved event when it does.
+fn update_ordered_balance(
+    store: &mut dyn Storage,
+    change: &BalanceChange,
+) -> Result<Option<BalanceChangeEvent>, ContractError> {
+    let BalanceChange {
+        address,
+        prev_balance,
+        next_balance,
+    } = change;
+
+    if prev_balance == next_balance {
+        return Ok(None);
+    }
+
+    ORDERED_BALANCES.remove(store, (prev_balance.u128(), address));
+    if !next_balance.is_zero() {
+        ORDERED_BALANCES.save(store, (next_balance.u128(), address), &0)?;
+    }
+
+    match (prev_balance.is_zero(), next_balance.is_zero()) {
+        (true, false) => N_BALANCES.update(store, |n| add_u64(n, 1u64))?,
+        (false, true) => N_BALANCES.update(store, |n| sub_u64(n, 1u64))?,
+        _ => return Ok(None),
+    };
+
+    Ok(BalanceChangeEvent::holder_set_change(
+        address,
+        *prev_balance,
+        *next_balance,
+    ))
+}
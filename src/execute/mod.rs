@@ -1,7 +1,15 @@
+pub mod after_burn;
+pub mod after_mint;
+pub mod after_transfer;
+pub mod after_update_allowance;
 pub mod before_burn;
 pub mod before_mint;
 pub mod before_transfer;
 pub mod before_update_marketing;
 pub mod before_upload_logo;
+pub mod hooks;
 pub mod operator;
+pub mod receive;
+pub mod retry_balance_change_notifications;
+pub mod subscribe;
 pub mod tf;
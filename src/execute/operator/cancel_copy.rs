@@ -0,0 +1,20 @@
+use crate::{
+    error::ContractError,
+    state::{BALANCE_COPY_CURSORS, BALANCE_COPY_JOB},
+};
+use cosmwasm_std::{attr, DepsMut, Response};
+
+/// Abandon the balance copy in progress so that another can be started.
+/// Balances already copied are left as they are.
+pub fn exec_cancel_copy(deps: DepsMut) -> Result<Response, ContractError> {
+    let (source, _) = BALANCE_COPY_CURSORS
+        .first(deps.storage)?
+        .ok_or_else(|| ContractError::ValidationError {
+            reason: "no balance copy in progress".to_owned(),
+        })?;
+
+    BALANCE_COPY_CURSORS.clear(deps.storage);
+    BALANCE_COPY_JOB.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![attr("action", "cancel_copy"), attr("source", source)]))
+}
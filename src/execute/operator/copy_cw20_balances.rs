@@ -1,105 +1,154 @@
 use crate::{
+    copy::{apply_supply_delta, load_copy_job, load_copy_source_kind, local_bech32_prefix, plan_copy_batch},
     error::ContractError,
-    math::{add_u128, add_u64},
-    msg::BalanceCopyMode,
-    state::{BALANCE_COPY_CURSORS, N_BALANCES, ORDERED_BALANCES},
+    execute::hooks::{after_balance_changes, load_balance_changes, HookContext},
+    history::update_history,
+    listeners::notify_balance_change_listeners,
+    math::add_u128,
+    msg::{
+        BalanceChangeEvent, BalanceChangeEventV2, BalanceChangeOperation, BalanceCopyMode, CopyCapBehavior, CopySource,
+    },
+    state::{BALANCE_COPY_CURSORS, BALANCE_COPY_JOB},
 };
-use cosmwasm_std::{attr, Addr, DepsMut, Response, Uint128};
-use cw20::{AllAccountsResponse, BalanceResponse, Cw20QueryMsg};
-use cw20_base::state::BALANCES;
-
-const ALL_BALANCES_QUERY_LIMIT: u32 = 30;
-const NUM_BALANCES_QUERY_PER_EXECUTION: u32 = 2;
-const LIMIT: u64 = 50;
+use cosmwasm_std::{attr, Addr, DepsMut, Env, Response, Storage, SubMsg, Uint128};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
 
 pub fn exec_copy_cw20_balances(
     deps: DepsMut,
-    other_cw20_addr: Addr,
+    env: Env,
+    source: CopySource,
     copy_mode: BalanceCopyMode,
+    cap_behavior: Option<CopyCapBehavior>,
+    remap_prefix: Option<bool>,
 ) -> Result<Response, ContractError> {
-    let mut cursor: Option<String> = BALANCE_COPY_CURSORS.may_load(deps.storage, &other_cw20_addr)?;
-
-    // Fail if we're already in the middle of copying some other CW20. Just one
-    // at a time, baby...
-    if !BALANCE_COPY_CURSORS.is_empty(deps.storage) && !BALANCE_COPY_CURSORS.has(deps.storage, &other_cw20_addr) {
-        return Err(ContractError::Unauthorized {
-            reason: format!(
-                "copy already in progress for {}",
-                BALANCE_COPY_CURSORS.first(deps.storage)?.unwrap().1.to_string()
-            ),
-        });
-    }
+    let source_key = source.key();
+    let source_kind = load_copy_source_kind(deps.as_ref(), &source)?;
+    let mut job = load_copy_job(
+        deps.storage,
+        &source,
+        &source_kind,
+        &copy_mode,
+        cap_behavior,
+        remap_prefix,
+    )?;
 
-    let mut t = 0;
-
-    for _ in 0..NUM_BALANCES_QUERY_PER_EXECUTION {
-        // fetch next batch of cw20 account address
-        let AllAccountsResponse { accounts } = deps.querier.query_wasm_smart(
-            deps.api.addr_validate(other_cw20_addr.as_str())?,
-            &Cw20QueryMsg::AllAccounts {
-                start_after: cursor,
-                limit: Some(ALL_BALANCES_QUERY_LIMIT),
-            },
-        )?;
-
-        // iterate over the cw20's balances in batches of 30, since this is the
-        // max limit on its all_accounts query :(
-        for address in accounts.iter() {
-            let BalanceResponse { balance } = deps.querier.query_wasm_smart(
-                other_cw20_addr.to_owned(),
-                &Cw20QueryMsg::Balance {
-                    address: address.to_owned(),
-                },
-            )?;
-
-            // Overwrite or increment existing balances based on copy mode
-            let address = Addr::unchecked(address);
-            let mut old_balance = Uint128::zero();
-            let new_balance = BALANCES.update(deps.storage, &address, |maybe_amount| -> Result<_, ContractError> {
-                old_balance = maybe_amount.unwrap_or_default();
-                Ok(match copy_mode {
-                    BalanceCopyMode::Replace => balance,
-                    BalanceCopyMode::Increment => add_u128(old_balance, balance)?,
-                })
-            })?;
-
-            // Update its ordered balances entry
-            ORDERED_BALANCES.remove(deps.storage, (old_balance.u128(), &address));
-            if !new_balance.is_zero() {
-                ORDERED_BALANCES.save(deps.storage, (new_balance.u128(), &address), &0)?;
-                if old_balance.is_zero() {
-                    N_BALANCES.update(deps.storage, |n| add_u64(n, 1u64))?;
-                }
-            }
-
-            t += 1;
-            if t == LIMIT {
-                break;
-            }
-        }
+    let prefix = match job.remap_prefix {
+        true => Some(local_bech32_prefix(&env)?),
+        false => None,
+    };
+    let batch = plan_copy_batch(
+        deps.as_ref(),
+        &source,
+        &source_kind,
+        &copy_mode,
+        &job.cap_behavior,
+        prefix.as_deref(),
+    )?;
+
+    // Copied balances are minted by the source CW20, or by this contract when
+    // importing a bank denom
+    let initiator = match &source {
+        CopySource::Cw20 { address } => address.to_owned(),
+        CopySource::Denom { .. } => env.contract.address.to_owned(),
+    };
+    let operation = BalanceChangeOperation::CopyBalances { source };
+
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    let mut submsgs: Vec<SubMsg> = vec![];
 
-        // update cursor
-        if accounts.len() == ALL_BALANCES_QUERY_LIMIT as usize {
-            cursor = accounts.last().and_then(|a| Some(a.to_owned()));
+    for change in batch.changes.iter() {
+        let ctx = HookContext::new(deps.storage, operation.to_owned(), &[&change.address])?;
+        BALANCES.save(deps.storage, &change.address, &change.next_balance)?;
+        token_info.total_supply = apply_supply_delta(token_info.total_supply, change)?;
+
+        job.n_accounts += 1;
+        if change.next_balance > change.prev_balance {
+            job.amount_added = add_u128(job.amount_added, change.next_balance - change.prev_balance)?;
         } else {
-            cursor = None;
-            break;
+            job.amount_removed = add_u128(job.amount_removed, change.prev_balance - change.next_balance)?;
         }
 
-        if t == LIMIT {
-            break;
-        }
+        submsgs.extend(after_copy_balance(
+            deps.storage,
+            &env,
+            &initiator,
+            ctx,
+            token_info.total_supply,
+        )?);
+    }
+
+    job.n_skipped += batch.skipped.len() as u64;
+
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    update_history(deps.storage, &env, None)?;
+
+    let mut attrs = vec![
+        attr("action", "copy_balances"),
+        attr("source_kind", format!("{:?}", source_kind)),
+        attr("n_accounts", job.n_accounts.to_string()),
+        attr("n_skipped", job.n_skipped.to_string()),
+        attr("total_supply", token_info.total_supply.to_string()),
+    ];
+
+    // Report the source addresses that couldn't be remapped in this batch
+    if !batch.skipped.is_empty() {
+        attrs.push(attr("skipped", batch.skipped.join(",")));
     }
-    let mut attrs = vec![attr("action", "copy_balances")];
 
     // Persist final cursor
-    if let Some(cursor) = &cursor {
-        BALANCE_COPY_CURSORS.save(deps.storage, &other_cw20_addr, cursor)?;
+    if let Some(cursor) = &batch.cursor {
+        BALANCE_COPY_CURSORS.save(deps.storage, source_key.as_str(), cursor)?;
+        BALANCE_COPY_JOB.save(deps.storage, &job)?;
         attrs.push(attr("done", "false"));
     } else {
-        BALANCE_COPY_CURSORS.remove(deps.storage, &other_cw20_addr);
+        BALANCE_COPY_CURSORS.remove(deps.storage, source_key.as_str());
+        BALANCE_COPY_JOB.remove(deps.storage);
         attrs.push(attr("done", "true"));
     }
 
-    Ok(Response::new().add_attributes(attrs))
+    Ok(Response::new().add_attributes(attrs).add_submessages(submsgs))
+}
+
+/// Sync derived state with an account's copied balance and notify listeners,
+/// presenting the change as a Mint from the initiator or, when a Replace
+/// lowers the balance, as a Burn.
+fn after_copy_balance(
+    store: &mut dyn Storage,
+    env: &Env,
+    initiator: &Addr,
+    ctx: HookContext,
+    total_supply: Uint128,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let changes = load_balance_changes(store, &ctx)?;
+    let change = &changes[0];
+
+    let event = if change.next_balance > change.prev_balance {
+        BalanceChangeEvent::Mint {
+            initiator: initiator.to_owned(),
+            recipient: change.address.to_owned(),
+            recipient_balance: change.next_balance,
+            amount: change.next_balance - change.prev_balance,
+        }
+    } else if change.next_balance < change.prev_balance {
+        BalanceChangeEvent::Burn {
+            initiator: change.address.to_owned(),
+            initiator_balance: change.next_balance,
+            amount: change.prev_balance - change.next_balance,
+        }
+    } else {
+        return Ok(vec![]);
+    };
+
+    let mut submsgs = notify_balance_change_listeners(
+        store,
+        &BalanceChangeEventV2::new(
+            env,
+            event,
+            ctx.operation.to_owned(),
+            ctx.prev_balances.to_owned(),
+            total_supply,
+        ),
+    )?;
+    submsgs.extend(after_balance_changes(store, env, &ctx, &changes, total_supply)?);
+    Ok(submsgs)
 }
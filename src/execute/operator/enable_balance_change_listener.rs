@@ -0,0 +1,30 @@
+use crate::{
+    error::ContractError,
+    listeners::load_listener_health,
+    state::{BALANCE_CHANGE_LISTENERS, LISTENER_HEALTH, SUBSCRIPTIONS},
+};
+use cosmwasm_std::{attr, Addr, DepsMut, Response};
+
+/// Re-enable a listener or subscriber that was disabled after repeated delivery failures,
+/// resetting its consecutive failure count.
+pub fn exec_enable_balance_change_listener(
+    deps: DepsMut,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    if !BALANCE_CHANGE_LISTENERS.has(deps.storage, &address) && !SUBSCRIPTIONS.has(deps.storage, &address) {
+        return Err(ContractError::ValidationError {
+            reason: format!("{} is not a registered listener or subscriber", address),
+        });
+    }
+
+    let mut health = load_listener_health(deps.storage, &address)?;
+    health.n_failures = 0;
+    health.last_failed_delivery = None;
+    health.disabled = false;
+    LISTENER_HEALTH.save(deps.storage, &address, &health)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "enable_balance_change_listener"),
+        attr("address", address.to_string()),
+    ]))
+}
@@ -1,44 +1,58 @@
 use crate::{
     error::ContractError,
+    listeners::notify_balance_change_listeners,
+    msg::{BalanceChangeEvent, BalanceChangeEventV2, BalanceChangeOperation},
     state::{FROZEN_ACCOUNTS, GLOBAL_BALANCE_FREEZE},
 };
-use cosmwasm_std::{attr, Addr, DepsMut, Event, Response};
-use cw20_base::state::BALANCES;
+use cosmwasm_std::{attr, Addr, DepsMut, Env, Event, Response, SubMsg};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
 
 pub fn exec_freeze(
     deps: DepsMut,
+    env: Env,
     addresses: Option<Vec<Addr>>,
 ) -> Result<Response, ContractError> {
+    let (events, submsgs) = toggle_freeze(deps, &env, addresses, true)?;
     Ok(Response::new()
         .add_attributes(vec![attr("action", "freeze")])
-        .add_events(toggle_freeze(deps, addresses, true)?))
+        .add_events(events)
+        .add_submessages(submsgs))
 }
 
 pub fn exec_unfreeze(
     deps: DepsMut,
+    env: Env,
     addresses: Option<Vec<Addr>>,
 ) -> Result<Response, ContractError> {
+    let (events, submsgs) = toggle_freeze(deps, &env, addresses, false)?;
     Ok(Response::new()
         .add_attributes(vec![attr("action", "unfreeze")])
-        .add_events(toggle_freeze(deps, addresses, false)?))
+        .add_events(events)
+        .add_submessages(submsgs))
 }
 
 /// Lock or unlock one or more specific account or, if none specified, lock or
-/// unlock all, globally.
+/// unlock all, globally. Returns events for the response along with submsgs
+/// notifying listeners of each freeze or unfreeze.
 pub fn toggle_freeze(
     deps: DepsMut,
+    env: &Env,
     addresses: Option<Vec<Addr>>,
     new_state: bool,
-) -> Result<Vec<Event>, ContractError> {
+) -> Result<(Vec<Event>, Vec<SubMsg>), ContractError> {
+    let mut changed: Vec<Option<Addr>> = vec![];
+    let mut events: Vec<Event> = vec![];
+
     if let Some(addresses) = addresses {
         // Lock specific accounts
-        let mut events: Vec<Event> = Vec::with_capacity(addresses.len());
+        events.reserve(addresses.len());
         if new_state {
             // We're setting locks
             for addr in addresses.iter() {
                 if BALANCES.has(deps.storage, &deps.api.addr_validate(addr.as_str())?) {
                     events.push(Event::new("lock-account").add_attribute("address", addr.to_string()));
-                    FROZEN_ACCOUNTS.save(deps.storage, &addr, &true)?;
+                    FROZEN_ACCOUNTS.save(deps.storage, addr, &true)?;
+                    changed.push(Some(addr.to_owned()));
                 }
             }
         } else {
@@ -46,11 +60,11 @@ pub fn toggle_freeze(
             for addr in addresses.iter() {
                 if BALANCES.has(deps.storage, &deps.api.addr_validate(addr.as_str())?) {
                     events.push(Event::new("unlock-account").add_attribute("address", addr.to_string()));
-                    FROZEN_ACCOUNTS.remove(deps.storage, &addr);
+                    FROZEN_ACCOUNTS.remove(deps.storage, addr);
+                    changed.push(Some(addr.to_owned()));
                 }
             }
         }
-        Ok(events)
     } else {
         // Lock or unlock all balances globally
         if new_state {
@@ -58,6 +72,26 @@ pub fn toggle_freeze(
         } else {
             GLOBAL_BALANCE_FREEZE.remove(deps.storage);
         }
-        Ok(vec![])
+        changed.push(None);
+    }
+
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(changed.len());
+
+    for address in changed {
+        let (event, operation) = if new_state {
+            (BalanceChangeEvent::Frozen { address }, BalanceChangeOperation::Freeze)
+        } else {
+            (
+                BalanceChangeEvent::Unfrozen { address },
+                BalanceChangeOperation::Unfreeze,
+            )
+        };
+        submsgs.extend(notify_balance_change_listeners(
+            deps.storage,
+            &BalanceChangeEventV2::new(env, event, operation, vec![], total_supply),
+        )?);
     }
+
+    Ok((events, submsgs))
 }
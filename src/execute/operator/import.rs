@@ -0,0 +1,244 @@
+use crate::{
+    error::ContractError,
+    execute::hooks::{index_balance_change, BalanceChange},
+    export::update_export_checksum,
+    math::add_u128,
+    msg::{ExportChecksum, ExportRecord, ImportState, Subscription},
+    state::{
+        ACCOUNT_STATS, BALANCE_CHANGE_LISTENERS, BALANCE_CHANGE_LISTENER_CONFIGS, EXCLUDED_ACCOUNTS, FROZEN_ACCOUNTS,
+        GLOBAL_BALANCE_FREEZE, HISTORY, IMPORT_STATE, LEGACY_TOKENS, LISTENER_HEALTH, OPERATOR_ADDR, ORDERED_SENDERS,
+        SUBSCRIPTIONS, SUBSCRIPTION_DEPOSIT, TIERS, TRANSFER_HISTORY, TRANSFER_HISTORY_SEQS, TRANSFER_HISTORY_SIZE,
+        WATCHERS,
+    },
+};
+use cosmwasm_std::{attr, DepsMut, MessageInfo, Response, Storage, Uint128};
+use cw20_base::state::{ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, LOGO, MARKETING_INFO, TOKEN_INFO};
+use std::collections::BTreeMap;
+
+/// Restore a page of exported records. The first page must start with the
+/// config and can only be imported into a contract without any balances. Each
+/// page's checksum is verified against the running checksum of every record
+/// imported so far, and the last page's balances against total supply. The
+/// funds sent must add up to the deposits of the page's subscriptions.
+pub fn exec_import(
+    deps: DepsMut,
+    info: MessageInfo,
+    records: Vec<ExportRecord>,
+    checksum: ExportChecksum,
+    done: bool,
+) -> Result<Response, ContractError> {
+    let mut state = match IMPORT_STATE.may_load(deps.storage)? {
+        Some(state) => {
+            if state.done {
+                return Err(ContractError::ValidationError {
+                    reason: "import already done".to_owned(),
+                });
+            }
+            state
+        },
+        None => {
+            if !BALANCES.is_empty(deps.storage) || !TOKEN_INFO.load(deps.storage)?.total_supply.is_zero() {
+                return Err(ContractError::ValidationError {
+                    reason: "can only import into a contract without balances".to_owned(),
+                });
+            }
+            ImportState {
+                checksum: ExportChecksum::default(),
+                operator: None,
+                done: false,
+            }
+        },
+    };
+
+    ensure_import_deposits(&info, &records)?;
+
+    for record in records.iter() {
+        // The config comes first, and only once
+        let is_config = matches!(record, ExportRecord::Config(_));
+        if is_config != (state.checksum.n_records == 0) {
+            return Err(ContractError::ValidationError {
+                reason: "import must start with the config, and include it only once".to_owned(),
+            });
+        }
+        update_export_checksum(&mut state.checksum, record)?;
+        import_record(deps.storage, &mut state, record)?;
+    }
+
+    if state.checksum != checksum {
+        return Err(ContractError::ValidationError {
+            reason: format!(
+                "checksum mismatch after {} records: expected {}, got {}",
+                state.checksum.n_records, checksum.digest, state.checksum.digest
+            ),
+        });
+    }
+
+    if done {
+        let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+        if state.checksum.balance_sum != total_supply {
+            return Err(ContractError::ValidationError {
+                reason: format!(
+                    "imported balances sum to {}, but total supply is {}",
+                    state.checksum.balance_sum, total_supply
+                ),
+            });
+        }
+        // Hand over to the exported operator only now, so that the importer
+        // keeps control until the last page
+        match &state.operator {
+            Some(operator) => OPERATOR_ADDR.save(deps.storage, operator)?,
+            None => OPERATOR_ADDR.remove(deps.storage),
+        }
+        state.done = true;
+    }
+
+    IMPORT_STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "import"),
+        attr("n_records", state.checksum.n_records.to_string()),
+        attr("checksum", state.checksum.digest.to_hex()),
+        attr("done", done.to_string()),
+    ]))
+}
+
+/// Require the funds sent to be exactly the deposits of the subscriptions in
+/// the page, which this contract refunds when they unsubscribe
+fn ensure_import_deposits(
+    info: &MessageInfo,
+    records: &[ExportRecord],
+) -> Result<(), ContractError> {
+    let mut deposits: BTreeMap<&str, Uint128> = BTreeMap::new();
+    for record in records.iter() {
+        if let ExportRecord::Subscription {
+            subscription: Subscription {
+                deposit: Some(deposit), ..
+            },
+            ..
+        } = record
+        {
+            let total = deposits.entry(deposit.denom.as_str()).or_default();
+            *total = add_u128(*total, deposit.amount)?;
+        }
+    }
+    deposits.retain(|_, amount| !amount.is_zero());
+
+    let funds: BTreeMap<&str, Uint128> = info
+        .funds
+        .iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .map(|coin| (coin.denom.as_str(), coin.amount))
+        .collect();
+
+    if funds != deposits {
+        return Err(ContractError::ValidationError {
+            reason: format!(
+                "funds must match the subscription deposits in the page: {}",
+                deposits
+                    .iter()
+                    .map(|(denom, amount)| format!("{}{}", amount, denom))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Write a record to storage, along with the indexes and aggregates derived
+/// from it
+fn import_record(
+    store: &mut dyn Storage,
+    state: &mut ImportState,
+    record: &ExportRecord,
+) -> Result<(), ContractError> {
+    match record {
+        ExportRecord::Config(config) => {
+            TOKEN_INFO.save(store, &config.token_info)?;
+            match &config.marketing_info {
+                Some(marketing_info) => MARKETING_INFO.save(store, marketing_info)?,
+                None => MARKETING_INFO.remove(store),
+            }
+            match &config.logo {
+                Some(logo) => LOGO.save(store, logo)?,
+                None => LOGO.remove(store),
+            }
+            GLOBAL_BALANCE_FREEZE.save(store, &config.global_freeze)?;
+            TIERS.save(store, &config.tiers)?;
+            TRANSFER_HISTORY_SIZE.save(store, &config.transfer_history_size)?;
+            match &config.subscription_deposit {
+                Some(deposit) => SUBSCRIPTION_DEPOSIT.save(store, deposit)?,
+                None => SUBSCRIPTION_DEPOSIT.remove(store),
+            }
+            for token in config.legacy_tokens.iter() {
+                LEGACY_TOKENS.save(store, &token.address, token)?;
+            }
+            state.operator = config.operator.to_owned();
+        },
+        ExportRecord::Balance { address, amount } => {
+            let prev_balance = BALANCES.may_load(store, address)?.unwrap_or_default();
+            BALANCES.save(store, address, amount)?;
+            index_balance_change(
+                store,
+                &BalanceChange {
+                    address: address.to_owned(),
+                    prev_balance,
+                    next_balance: *amount,
+                },
+            )?;
+        },
+        ExportRecord::Allowance {
+            owner,
+            spender,
+            allowance,
+        } => {
+            ALLOWANCES.save(store, (owner, spender), allowance)?;
+            ALLOWANCES_SPENDER.save(store, (spender, owner), allowance)?;
+        },
+        ExportRecord::Frozen { address, frozen } => {
+            FROZEN_ACCOUNTS.save(store, address, frozen)?;
+        },
+        ExportRecord::Excluded { address } => {
+            EXCLUDED_ACCOUNTS.save(store, address, &true)?;
+        },
+        ExportRecord::Listener {
+            address,
+            registration,
+            config,
+            health,
+        } => {
+            BALANCE_CHANGE_LISTENERS.save(store, address, registration)?;
+            if let Some(config) = config {
+                BALANCE_CHANGE_LISTENER_CONFIGS.save(store, address, config)?;
+            }
+            if let Some(health) = health {
+                LISTENER_HEALTH.save(store, address, health)?;
+            }
+        },
+        ExportRecord::Subscription {
+            subscriber,
+            subscription,
+        } => {
+            // The deposit was sent with the page, see ensure_import_deposits
+            SUBSCRIPTIONS.save(store, subscriber, subscription)?;
+            for addr in subscription.watched.iter() {
+                WATCHERS.save(store, (addr, subscriber), &0)?;
+            }
+        },
+        ExportRecord::AccountStats { address, stats } => {
+            ACCOUNT_STATS.save(store, address, stats)?;
+            if !stats.n_sent.is_zero() {
+                ORDERED_SENDERS.save(store, (stats.n_sent.u64(), address), &0)?;
+            }
+        },
+        ExportRecord::History { day, snapshot } => {
+            HISTORY.save(store, *day, snapshot)?;
+        },
+        ExportRecord::TransferRecord { address, seq, record } => {
+            TRANSFER_HISTORY.save(store, (address, *seq), record)?;
+            // Records are exported in ascending order of seq per account
+            TRANSFER_HISTORY_SEQS.save(store, address, &(seq + 1))?;
+        },
+    }
+    Ok(())
+}
@@ -1,5 +1,14 @@
+pub mod cancel_copy;
 pub mod copy_cw20_balances;
+pub mod enable_balance_change_listener;
 pub mod freeze;
+pub mod import;
+pub mod reindex;
 pub mod remove_operator;
 pub mod set_operator;
+pub mod set_subscription_deposit;
+pub mod set_tiers;
+pub mod set_transfer_history_size;
 pub mod update_balance_change_listeners;
+pub mod update_excluded_accounts;
+pub mod update_legacy_tokens;
@@ -0,0 +1,111 @@
+use crate::{
+    error::ContractError,
+    execute::hooks::{index_balance_change, BalanceChange},
+    msg::{ReindexPhase, ReindexState},
+    state::{BALANCE_HISTOGRAM, N_BALANCES, N_TIER_HOLDERS, ORDERED_BALANCES, REINDEX_STATE, SUM_SQUARED_BALANCES},
+};
+use cosmwasm_std::{attr, DepsMut, Event, Order, Response, Storage, Uint128, Uint256, Uint64};
+use cw20_base::state::BALANCES;
+use cw_storage_plus::Bound;
+
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 500;
+
+/// Start a Reindex from the beginning, discarding any progress of one already
+/// underway. The operator then completes it by repeatedly calling Reindex.
+pub fn start_reindex(store: &mut dyn Storage) -> Result<(), ContractError> {
+    REINDEX_STATE.save(store, &new_reindex_state())?;
+    Ok(())
+}
+
+fn new_reindex_state() -> ReindexState {
+    ReindexState {
+        phase: ReindexPhase::Clearing,
+        cursor: None,
+        n_cleared: 0,
+        n_indexed: 0,
+    }
+}
+
+/// Rebuild ORDERED_BALANCES, N_BALANCES and the stats and tier counts derived
+/// from them out of BALANCES. Each call processes one batch: first the old
+/// index is cleared, then every account is re-indexed in address order. Ranked
+/// queries are blocked until the final batch completes.
+pub fn exec_reindex(
+    deps: DepsMut,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let mut state = REINDEX_STATE.may_load(deps.storage)?.unwrap_or_else(new_reindex_state);
+
+    let done = match state.phase {
+        ReindexPhase::Clearing => {
+            let keys = ORDERED_BALANCES
+                .keys(deps.storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<Result<Vec<_>, _>>()?;
+            for (amount, address) in keys.iter() {
+                ORDERED_BALANCES.remove(deps.storage, (*amount, address));
+            }
+            state.n_cleared += keys.len() as u64;
+
+            // Once the index is empty, reset the aggregates derived from it
+            if keys.len() < limit {
+                N_BALANCES.save(deps.storage, &Uint64::zero())?;
+                SUM_SQUARED_BALANCES.save(deps.storage, &Uint256::zero())?;
+                BALANCE_HISTOGRAM.clear(deps.storage);
+                N_TIER_HOLDERS.clear(deps.storage);
+                state.phase = ReindexPhase::Indexing;
+            }
+            false
+        },
+        ReindexPhase::Indexing => {
+            let entries = BALANCES
+                .range(
+                    deps.storage,
+                    state.cursor.as_ref().map(Bound::exclusive),
+                    None,
+                    Order::Ascending,
+                )
+                .take(limit)
+                .collect::<Result<Vec<_>, _>>()?;
+            for (address, balance) in entries.iter() {
+                if !balance.is_zero() {
+                    index_balance_change(
+                        deps.storage,
+                        &BalanceChange {
+                            address: address.to_owned(),
+                            prev_balance: Uint128::zero(),
+                            next_balance: *balance,
+                        },
+                    )?;
+                    state.n_indexed += 1;
+                }
+            }
+            if let Some((address, _)) = entries.last() {
+                state.cursor = Some(address.to_owned());
+            }
+            entries.len() < limit
+        },
+    };
+
+    if done {
+        REINDEX_STATE.remove(deps.storage);
+    } else {
+        REINDEX_STATE.save(deps.storage, &state)?;
+    }
+
+    let phase = match state.phase {
+        ReindexPhase::Clearing => "clearing",
+        ReindexPhase::Indexing => "indexing",
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![attr("action", "reindex")])
+        .add_event(Event::new("reindex").add_attributes(vec![
+            attr("phase", phase),
+            attr("n_cleared", state.n_cleared.to_string()),
+            attr("n_indexed", state.n_indexed.to_string()),
+            attr("done", done.to_string()),
+        ])))
+}
@@ -0,0 +1,22 @@
+use crate::{error::ContractError, state::SUBSCRIPTION_DEPOSIT};
+use cosmwasm_std::{attr, Coin, DepsMut, Response};
+
+/// Set or clear the deposit required to create a new subscription. Existing
+/// subscriptions keep the deposit they were created with.
+pub fn exec_set_subscription_deposit(
+    deps: DepsMut,
+    deposit: Option<Coin>,
+) -> Result<Response, ContractError> {
+    match &deposit {
+        Some(coin) if !coin.amount.is_zero() => SUBSCRIPTION_DEPOSIT.save(deps.storage, coin)?,
+        _ => SUBSCRIPTION_DEPOSIT.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_subscription_deposit"),
+        attr(
+            "deposit",
+            deposit.map(|c| c.to_string()).unwrap_or_else(|| "none".to_owned()),
+        ),
+    ]))
+}
@@ -0,0 +1,59 @@
+use std::collections::HashSet;
+
+use crate::{
+    checks::ensure_index_ready,
+    error::ContractError,
+    execute::operator::reindex::start_reindex,
+    msg::HolderTier,
+    state::{MAX_TIERS, TIERS},
+};
+use cosmwasm_std::{attr, DepsMut, Response};
+
+/// Replace the holder tier configuration and start a Reindex to recount the
+/// number of holders in each tier. Tier queries are blocked until the operator
+/// completes the Reindex.
+pub fn exec_set_tiers(
+    deps: DepsMut,
+    mut tiers: Vec<HolderTier>,
+) -> Result<Response, ContractError> {
+    // Don't restart a Reindex that's already underway
+    ensure_index_ready(deps.storage)?;
+
+    if tiers.len() > MAX_TIERS {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot configure more than {} tiers", MAX_TIERS),
+        });
+    }
+
+    tiers.sort_by_key(|t| t.min_balance);
+
+    let mut names: HashSet<String> = HashSet::with_capacity(tiers.len());
+    for (i, tier) in tiers.iter().enumerate() {
+        if tier.min_balance.is_zero() {
+            return Err(ContractError::ValidationError {
+                reason: format!("tier {} min_balance must be greater than zero", tier.name),
+            });
+        }
+        if i > 0 && tiers[i - 1].min_balance == tier.min_balance {
+            return Err(ContractError::ValidationError {
+                reason: format!("duplicate tier min_balance {}", tier.min_balance),
+            });
+        }
+        if !names.insert(tier.name.to_owned()) {
+            return Err(ContractError::ValidationError {
+                reason: format!("duplicate tier name {}", tier.name),
+            });
+        }
+    }
+
+    TIERS.save(deps.storage, &tiers)?;
+
+    // Recount holders per tier in batches through the Reindex, which clears
+    // N_TIER_HOLDERS and re-indexes every balance against the new tiers
+    start_reindex(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_tiers"),
+        attr("n_tiers", tiers.len().to_string()),
+    ]))
+}
@@ -0,0 +1,25 @@
+use crate::{
+    error::ContractError,
+    state::{MAX_TRANSFER_HISTORY_SIZE, TRANSFER_HISTORY_SIZE},
+};
+use cosmwasm_std::{attr, DepsMut, Response};
+
+/// Set the number of recent transfers kept per account. Zero turns off
+/// recording altogether.
+pub fn exec_set_transfer_history_size(
+    deps: DepsMut,
+    size: u16,
+) -> Result<Response, ContractError> {
+    if size > MAX_TRANSFER_HISTORY_SIZE {
+        return Err(ContractError::ValidationError {
+            reason: format!("transfer history size cannot exceed {}", MAX_TRANSFER_HISTORY_SIZE),
+        });
+    }
+
+    TRANSFER_HISTORY_SIZE.save(deps.storage, &size)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_transfer_history_size"),
+        attr("size", size.to_string()),
+    ]))
+}
@@ -1,29 +1,78 @@
-use std::collections::HashSet;
-
-use crate::{error::ContractError, state::BALANCE_CHANGE_LISTENERS};
-use cosmwasm_std::{attr, Addr, DepsMut, Response};
+use crate::{
+    error::ContractError,
+    listeners::clear_listener_retries,
+    msg::{BalanceChangeListenerConfig, ListenerRegistration},
+    state::{BALANCE_CHANGE_LISTENERS, BALANCE_CHANGE_LISTENER_CONFIGS, LISTENER_HEALTH, MAX_FILTER_ADDRESSES},
+};
+use cosmwasm_std::{attr, Addr, DepsMut, Env, Response, Storage};
 
 pub fn exec_update_balance_change_listeners(
     deps: DepsMut,
+    env: Env,
     add: Option<Vec<Addr>>,
     remove: Option<Vec<Addr>>,
+    configs: Option<Vec<BalanceChangeListenerConfig>>,
 ) -> Result<Response, ContractError> {
-    let mut addr_set: HashSet<Addr> = HashSet::with_capacity(8);
-    let existing_addrs = BALANCE_CHANGE_LISTENERS.load(deps.storage)?;
-
-    addr_set.extend(existing_addrs);
-
     for addr in add.unwrap_or_default() {
-        addr_set.insert(deps.api.addr_validate(&addr.as_str())?);
+        let addr = deps.api.addr_validate(addr.as_str())?;
+        register_listener(deps.storage, &env, &addr)?;
     }
 
-    for addr in remove.unwrap_or_default() {
-        addr_set.remove(&addr);
+    for config in configs.unwrap_or_default() {
+        let addr = deps.api.addr_validate(config.address.as_str())?;
+        let config = BalanceChangeListenerConfig {
+            address: addr.to_owned(),
+            ..config
+        };
+        let n_filter_addresses = config
+            .filter
+            .as_ref()
+            .and_then(|f| f.addresses.as_ref())
+            .map(|a| a.len())
+            .unwrap_or_default();
+        if n_filter_addresses > MAX_FILTER_ADDRESSES {
+            return Err(ContractError::ValidationError {
+                reason: format!(
+                    "listener filter cannot watch more than {} addresses",
+                    MAX_FILTER_ADDRESSES
+                ),
+            });
+        }
+        if config.gas_limit == Some(0) {
+            return Err(ContractError::ValidationError {
+                reason: "listener gas limit must be greater than zero".to_owned(),
+            });
+        }
+        BALANCE_CHANGE_LISTENER_CONFIGS.save(deps.storage, &addr, &config)?;
+        register_listener(deps.storage, &env, &addr)?;
     }
 
-    let computed_listener_addrs: Vec<Addr> = Vec::from_iter(addr_set);
-
-    BALANCE_CHANGE_LISTENERS.save(deps.storage, &computed_listener_addrs)?;
+    for addr in remove.unwrap_or_default() {
+        BALANCE_CHANGE_LISTENER_CONFIGS.remove(deps.storage, &addr);
+        LISTENER_HEALTH.remove(deps.storage, &addr);
+        BALANCE_CHANGE_LISTENERS.remove(deps.storage, &addr);
+        clear_listener_retries(deps.storage, &addr)?;
+    }
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_balance_change_listeners")]))
 }
+
+/// Register the listener at the current block, keeping the original
+/// registration block of one that's already registered
+fn register_listener(
+    store: &mut dyn Storage,
+    env: &Env,
+    addr: &Addr,
+) -> Result<(), ContractError> {
+    if !BALANCE_CHANGE_LISTENERS.has(store, addr) {
+        BALANCE_CHANGE_LISTENERS.save(
+            store,
+            addr,
+            &ListenerRegistration {
+                height: env.block.height,
+                time: env.block.time,
+            },
+        )?;
+    }
+    Ok(())
+}
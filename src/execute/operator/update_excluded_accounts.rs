@@ -0,0 +1,35 @@
+use crate::{
+    error::ContractError,
+    state::{EXCLUDED_ACCOUNTS, MAX_EXCLUDED_ACCOUNTS},
+};
+use cosmwasm_std::{attr, Addr, DepsMut, Order, Response};
+
+pub fn exec_update_excluded_accounts(
+    deps: DepsMut,
+    add: Option<Vec<Addr>>,
+    remove: Option<Vec<Addr>>,
+) -> Result<Response, ContractError> {
+    for addr in remove.unwrap_or_default() {
+        EXCLUDED_ACCOUNTS.remove(deps.storage, &addr);
+    }
+
+    for addr in add.unwrap_or_default() {
+        EXCLUDED_ACCOUNTS.save(deps.storage, &deps.api.addr_validate(addr.as_str())?, &true)?;
+    }
+
+    // Keep the registry small enough to be scanned in full by supply queries
+    let n_excluded = EXCLUDED_ACCOUNTS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+
+    if n_excluded > MAX_EXCLUDED_ACCOUNTS {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot exclude more than {} accounts", MAX_EXCLUDED_ACCOUNTS),
+        });
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_excluded_accounts"),
+        attr("n_excluded", n_excluded.to_string()),
+    ]))
+}
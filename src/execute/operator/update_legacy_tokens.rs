@@ -0,0 +1,59 @@
+use crate::{
+    error::ContractError,
+    msg::{LegacyToken, LegacyTokenDisposal},
+    state::{LEGACY_TOKENS, MAX_LEGACY_TOKENS},
+};
+use cosmwasm_std::{attr, Addr, Decimal, DepsMut, Env, Order, Response};
+
+pub fn exec_update_legacy_tokens(
+    deps: DepsMut,
+    env: Env,
+    add: Option<Vec<LegacyToken>>,
+    remove: Option<Vec<Addr>>,
+) -> Result<Response, ContractError> {
+    for addr in remove.unwrap_or_default() {
+        LEGACY_TOKENS.remove(deps.storage, &addr);
+    }
+
+    for token in add.unwrap_or_default() {
+        let address = deps.api.addr_validate(token.address.as_str())?;
+        if address == env.contract.address {
+            return Err(ContractError::ValidationError {
+                reason: "cannot swap this token for itself".to_owned(),
+            });
+        }
+        if token.ratio.map(|r| r.is_zero()).unwrap_or(false) {
+            return Err(ContractError::ValidationError {
+                reason: format!("swap ratio for {} must be greater than {}", address, Decimal::zero()),
+            });
+        }
+        let disposal = match token.disposal {
+            Some(LegacyTokenDisposal::Forward { recipient }) => Some(LegacyTokenDisposal::Forward {
+                recipient: deps.api.addr_validate(recipient.as_str())?,
+            }),
+            disposal => disposal,
+        };
+        LEGACY_TOKENS.save(
+            deps.storage,
+            &address,
+            &LegacyToken {
+                address: address.to_owned(),
+                disposal,
+                ..token
+            },
+        )?;
+    }
+
+    let n_legacy_tokens = LEGACY_TOKENS.keys(deps.storage, None, None, Order::Ascending).count();
+
+    if n_legacy_tokens > MAX_LEGACY_TOKENS {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot accept more than {} legacy tokens", MAX_LEGACY_TOKENS),
+        });
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_legacy_tokens"),
+        attr("n_legacy_tokens", n_legacy_tokens.to_string()),
+    ]))
+}
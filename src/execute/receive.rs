@@ -0,0 +1,87 @@
+use crate::{
+    error::ContractError,
+    execute::{after_mint::after_mint, before_mint::before_mint},
+    math::add_u128,
+    msg::{LegacyTokenDisposal, ReceiveMsg},
+    state::LEGACY_TOKENS,
+};
+use cosmwasm_std::{attr, from_json, to_json_binary, Decimal, DepsMut, Env, MessageInfo, Response, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+/// Swap legacy tokens sent to this contract for new tokens, minted to the
+/// recipient through the same hooks as a regular mint, and then hold, burn or
+/// forward the legacy tokens as configured.
+pub fn exec_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let legacy_token =
+        LEGACY_TOKENS
+            .may_load(deps.storage, &info.sender)?
+            .ok_or_else(|| ContractError::Unauthorized {
+                reason: format!("{} is not an accepted legacy token", info.sender),
+            })?;
+
+    let recipient = match from_json(&receive_msg.msg)? {
+        ReceiveMsg::Migrate { recipient } => recipient.unwrap_or(receive_msg.sender.to_owned()),
+    };
+
+    let amount = receive_msg
+        .amount
+        .mul_floor(legacy_token.ratio.unwrap_or(Decimal::one()));
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // Mint directly rather than through cw20-base, since this contract isn't
+    // necessarily the minter, but keep within the minter cap
+    let ctx = before_mint(deps.storage, deps.api, &recipient)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    token_info.total_supply = add_u128(token_info.total_supply, amount)?;
+    if let Some(cap) = token_info.get_cap() {
+        if token_info.total_supply > cap {
+            return Err(ContractError::CannotExceedCap {});
+        }
+    }
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+    BALANCES.update(deps.storage, &recipient, |balance| {
+        add_u128(balance.unwrap_or_default(), amount)
+    })?;
+
+    let submsgs = after_mint(deps.storage, &env, ctx, &info.sender, amount)?;
+
+    let mut resp = Response::new()
+        .add_attributes(vec![
+            attr("action", "migrate"),
+            attr("legacy_token", info.sender.to_string()),
+            attr("sender", receive_msg.sender),
+            attr("recipient", recipient.to_string()),
+            attr("amount_received", receive_msg.amount.to_string()),
+            attr("amount", amount.to_string()),
+        ])
+        .add_submessages(submsgs);
+
+    let disposal_msg = match legacy_token.disposal.unwrap_or_default() {
+        LegacyTokenDisposal::Hold => None,
+        LegacyTokenDisposal::Burn => Some(Cw20ExecuteMsg::Burn {
+            amount: receive_msg.amount,
+        }),
+        LegacyTokenDisposal::Forward { recipient } => Some(Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: receive_msg.amount,
+        }),
+    };
+    if let Some(msg) = disposal_msg {
+        resp = resp.add_message(WasmMsg::Execute {
+            contract_addr: info.sender.to_string(),
+            msg: to_json_binary(&msg)?,
+            funds: vec![],
+        });
+    }
+
+    Ok(resp)
+}
@@ -0,0 +1,44 @@
+use crate::{
+    error::ContractError,
+    listeners::{build_listener_submsg, dequeue_listener_retries, load_listener_health},
+};
+use cosmwasm_std::{attr, Addr, DepsMut, Env, Response, SubMsg};
+
+const DEFAULT_RETRY_LIMIT: u16 = 10;
+const MAX_RETRY_LIMIT: u16 = 50;
+
+/// Permissionless crank that redelivers queued events to a listener, oldest
+/// first. Events that fail again go back into the queue.
+pub fn exec_retry_balance_change_notifications(
+    deps: DepsMut,
+    env: Env,
+    listener: Addr,
+    limit: Option<u16>,
+) -> Result<Response, ContractError> {
+    if load_listener_health(deps.storage, &listener)?.disabled {
+        return Err(ContractError::Unauthorized {
+            reason: format!("listener {} is disabled", listener),
+        });
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_RETRY_LIMIT).clamp(1, MAX_RETRY_LIMIT) as usize;
+    let queued_events = dequeue_listener_retries(deps.storage, &listener, limit)?;
+
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(queued_events.len());
+    for queued in queued_events {
+        submsgs.push(build_listener_submsg(
+            deps.storage,
+            &listener,
+            queued,
+            env.block.height,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "retry_balance_change_notifications"),
+            attr("listener", listener.to_string()),
+            attr("n_events", submsgs.len().to_string()),
+        ])
+        .add_submessages(submsgs))
+}
@@ -0,0 +1,217 @@
+use std::collections::HashSet;
+
+use crate::{
+    error::ContractError,
+    listeners::clear_listener_retries,
+    msg::{BalanceChangeEventVersion, Subscription},
+    state::{
+        EVICTED_SUBSCRIBERS, MAX_SUBSCRIBERS_PER_ADDRESS, MAX_SUBSCRIBER_GAS_LIMIT, MAX_SUBSCRIPTION_ADDRESSES,
+        OPERATOR_ADDR, SUBSCRIPTIONS, SUBSCRIPTION_DEPOSIT, WATCHERS,
+    },
+};
+use cosmwasm_std::{attr, Addr, BankMsg, DepsMut, Env, MessageInfo, Order, Response, Storage};
+use cw_utils::{must_pay, nonpayable};
+
+/// Subscribe the sending contract to balance change events that affect any of
+/// the watched addresses, replacing its existing subscription. A deposit is
+/// required for new subscriptions if the operator has set one. Subscribing
+/// again doesn't re-enable a subscriber disabled by failures; only the
+/// operator can. Addresses the subscriber was evicted from can't be watched
+/// until their account allows it again.
+pub fn exec_subscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    watched: Vec<Addr>,
+    event_version: Option<BalanceChangeEventVersion>,
+    gas_limit: u64,
+) -> Result<Response, ContractError> {
+    let subscriber = info.sender.to_owned();
+
+    if gas_limit == 0 || gas_limit > MAX_SUBSCRIBER_GAS_LIMIT {
+        return Err(ContractError::ValidationError {
+            reason: format!("gas limit must be between 1 and {}", MAX_SUBSCRIBER_GAS_LIMIT),
+        });
+    }
+
+    // Only contracts can receive OnBalanceChange callbacks
+    deps.querier
+        .query_wasm_contract_info(subscriber.to_string())
+        .map_err(|_| ContractError::Unauthorized {
+            reason: "only contracts can subscribe".to_owned(),
+        })?;
+
+    let watched: HashSet<Addr> = watched
+        .iter()
+        .map(|a| deps.api.addr_validate(a.as_str()))
+        .collect::<Result<_, _>>()?;
+
+    if watched.is_empty() || watched.len() > MAX_SUBSCRIPTION_ADDRESSES {
+        return Err(ContractError::ValidationError {
+            reason: format!("must watch between 1 and {} addresses", MAX_SUBSCRIPTION_ADDRESSES),
+        });
+    }
+
+    // Take a deposit for new subscriptions, carrying over the existing one
+    // for updates.
+    let deposit = match SUBSCRIPTIONS.may_load(deps.storage, &subscriber)? {
+        Some(prev) => {
+            nonpayable(&info).map_err(|e| ContractError::ValidationError { reason: e.to_string() })?;
+            for addr in prev.watched.iter() {
+                WATCHERS.remove(deps.storage, (addr, &subscriber));
+            }
+            prev.deposit
+        },
+        None => match SUBSCRIPTION_DEPOSIT.may_load(deps.storage)? {
+            Some(required) => {
+                let amount = must_pay(&info, &required.denom)
+                    .map_err(|e| ContractError::ValidationError { reason: e.to_string() })?;
+                if amount != required.amount {
+                    return Err(ContractError::ValidationError {
+                        reason: format!("subscription deposit must be {}", required),
+                    });
+                }
+                Some(required)
+            },
+            None => {
+                nonpayable(&info).map_err(|e| ContractError::ValidationError { reason: e.to_string() })?;
+                None
+            },
+        },
+    };
+
+    for addr in watched.iter() {
+        if EVICTED_SUBSCRIBERS.has(deps.storage, (addr, &subscriber)) {
+            return Err(ContractError::Unauthorized {
+                reason: format!("{} was evicted from watching {}", subscriber, addr),
+            });
+        }
+        let n_watchers = WATCHERS
+            .prefix(addr)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(MAX_SUBSCRIBERS_PER_ADDRESS)
+            .count();
+        if n_watchers >= MAX_SUBSCRIBERS_PER_ADDRESS {
+            return Err(ContractError::ValidationError {
+                reason: format!("{} already has the max number of subscribers", addr),
+            });
+        }
+        WATCHERS.save(deps.storage, (addr, &subscriber), &0)?;
+    }
+
+    SUBSCRIPTIONS.save(
+        deps.storage,
+        &subscriber,
+        &Subscription {
+            watched: watched.into_iter().collect(),
+            event_version,
+            deposit,
+            gas_limit: Some(gas_limit),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "subscribe"),
+        attr("subscriber", subscriber.to_string()),
+    ]))
+}
+
+/// Cancel the sender's subscription and refund its deposit. Its delivery
+/// health is kept, so that a subscriber disabled by failures stays disabled if
+/// it subscribes again, but its undelivered events are discarded.
+pub fn exec_unsubscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let subscriber = info.sender;
+    let subscription = SUBSCRIPTIONS.load(deps.storage, &subscriber)?;
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "unsubscribe"),
+        attr("subscriber", subscriber.to_string()),
+    ]);
+    cancel_subscription(deps.storage, resp, &subscriber, subscription)
+}
+
+/// Evict a subscriber, so that watched accounts and the operator can free up
+/// the limited subscriber slots of an address. A watched account only removes
+/// itself from the subscription, which is cancelled once it watches nothing,
+/// while the operator cancels the whole subscription. Either way, the
+/// subscriber can't watch the addresses it was evicted from again until their
+/// accounts allow it with AllowSubscriber.
+pub fn exec_evict_subscriber(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    subscriber: Addr,
+) -> Result<Response, ContractError> {
+    let mut subscription = SUBSCRIPTIONS.load(deps.storage, &subscriber)?;
+    let is_operator = OPERATOR_ADDR.may_load(deps.storage)? == Some(info.sender.to_owned());
+
+    let resp = Response::new().add_attributes(vec![
+        attr("action", "evict_subscriber"),
+        attr("subscriber", subscriber.to_string()),
+        attr("evicted_by", info.sender.to_string()),
+    ]);
+
+    if !is_operator {
+        if !subscription.watched.contains(&info.sender) {
+            return Err(ContractError::Unauthorized {
+                reason: format!("{} is not watched by {}", info.sender, subscriber),
+            });
+        }
+        subscription.watched.retain(|addr| *addr != info.sender);
+        WATCHERS.remove(deps.storage, (&info.sender, &subscriber));
+        EVICTED_SUBSCRIBERS.save(deps.storage, (&info.sender, &subscriber), &env.block.height)?;
+        if !subscription.watched.is_empty() {
+            SUBSCRIPTIONS.save(deps.storage, &subscriber, &subscription)?;
+            return Ok(resp);
+        }
+    } else {
+        for addr in subscription.watched.iter() {
+            EVICTED_SUBSCRIBERS.save(deps.storage, (addr, &subscriber), &env.block.height)?;
+        }
+    }
+
+    cancel_subscription(deps.storage, resp, &subscriber, subscription)
+}
+
+/// Let a subscriber evicted from watching the sender's address watch it again
+pub fn exec_allow_subscriber(
+    deps: DepsMut,
+    info: MessageInfo,
+    subscriber: Addr,
+) -> Result<Response, ContractError> {
+    if !EVICTED_SUBSCRIBERS.has(deps.storage, (&info.sender, &subscriber)) {
+        return Err(ContractError::ValidationError {
+            reason: format!("{} was not evicted from watching {}", subscriber, info.sender),
+        });
+    }
+    EVICTED_SUBSCRIBERS.remove(deps.storage, (&info.sender, &subscriber));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "allow_subscriber"),
+        attr("subscriber", subscriber.to_string()),
+        attr("allowed_by", info.sender.to_string()),
+    ]))
+}
+
+/// Remove the subscription and its retry queue, refunding any deposit
+fn cancel_subscription(
+    store: &mut dyn Storage,
+    resp: Response,
+    subscriber: &Addr,
+    subscription: Subscription,
+) -> Result<Response, ContractError> {
+    for addr in subscription.watched.iter() {
+        WATCHERS.remove(store, (addr, subscriber));
+    }
+    SUBSCRIPTIONS.remove(store, subscriber);
+    clear_listener_retries(store, subscriber)?;
+
+    Ok(match subscription.deposit {
+        Some(deposit) => resp.add_message(BankMsg::Send {
+            to_address: subscriber.to_string(),
+            amount: vec![deposit],
+        }),
+        None => resp,
+    })
+}
@@ -13,8 +13,8 @@ pub fn exec_tf_burn(
     let denom = TF_FULL_DENOM.load(deps.storage)?;
 
     TF_AMOUNT_BURNED.update(deps.storage, |n| -> Result<_, ContractError> {
-        Ok(n.checked_add(amount.into())
-            .map_err(|e| ContractError::Std(StdError::overflow(e)))?)
+        n.checked_add(amount.into())
+            .map_err(|e| ContractError::Std(StdError::overflow(e)))
     })?;
 
     Ok(Response::new()
@@ -29,13 +29,13 @@ pub fn exec_tf_derive_balances(
     let min_bound = deps
         .api
         .addr_validate(cursor_addr.as_str())
-        .and_then(|_| Ok(Some(Bound::Exclusive((&cursor_addr, PhantomData)))))
+        .map(|_| Some(Bound::Exclusive((&cursor_addr, PhantomData))))
         .unwrap_or(None);
 
     let limit = limit
-        .and_then(|x| Some(x as usize))
+        .map(|x| x as usize)
         .unwrap_or(DEFAULT_BALANCE_BATCH_SIZE)
-        .clamp(1, MAX_BALANCE_BATCH_SIZE) as usize;
+        .clamp(1, MAX_BALANCE_BATCH_SIZE);
 
     // Build next batch of recipient addrs & amnounts (initial balances)
     let mut recipients: Vec<(Addr, Uint128)> = Vec::with_capacity(N_BALANCES.load(deps.storage)?.u64() as usize);
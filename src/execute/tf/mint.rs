@@ -49,7 +49,7 @@ pub fn exec_tf_mint(
 pub fn mint_multiple(
     store: &mut dyn Storage,
     env_contract_addr: &Addr,
-    recipients: &Vec<(Addr, Uint128)>,
+    recipients: &[(Addr, Uint128)],
 ) -> Result<(Uint128, Vec<SubMsg>), ContractError> {
     let denom = TF_FULL_DENOM.load(store)?;
     let factory = TF_FACTORY.load(store)?;
@@ -0,0 +1,40 @@
+use cosmwasm_std::{to_json_vec, HexBinary};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::ContractError,
+    math::add_u128,
+    msg::{ExportChecksum, ExportRecord, ExportSection},
+};
+
+/// Section that Export moves on to once the given one is exhausted
+pub fn next_export_section(section: &ExportSection) -> Option<ExportSection> {
+    match section {
+        ExportSection::Config => Some(ExportSection::Balances),
+        ExportSection::Balances => Some(ExportSection::Allowances),
+        ExportSection::Allowances => Some(ExportSection::FrozenAccounts),
+        ExportSection::FrozenAccounts => Some(ExportSection::ExcludedAccounts),
+        ExportSection::ExcludedAccounts => Some(ExportSection::Listeners),
+        ExportSection::Listeners => Some(ExportSection::Subscriptions),
+        ExportSection::Subscriptions => Some(ExportSection::AccountStats),
+        ExportSection::AccountStats => Some(ExportSection::History),
+        ExportSection::History => Some(ExportSection::TransferHistory),
+        ExportSection::TransferHistory => None,
+    }
+}
+
+/// Add a record to the running checksum, chaining the digest over its JSON
+pub fn update_export_checksum(
+    checksum: &mut ExportChecksum,
+    record: &ExportRecord,
+) -> Result<(), ContractError> {
+    let mut hasher = Sha256::new();
+    hasher.update(checksum.digest.as_slice());
+    hasher.update(to_json_vec(record)?);
+    checksum.digest = HexBinary::from(hasher.finalize().as_slice());
+    checksum.n_records += 1;
+    if let ExportRecord::Balance { amount, .. } = record {
+        checksum.balance_sum = add_u128(checksum.balance_sum, *amount)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Env, Order, Storage, Uint128, Uint64};
+use cw20_base::state::TOKEN_INFO;
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    math::{add_u128, add_u64},
+    msg::HistorySnapshot,
+    state::{HISTORY, MAX_HISTORY_DAYS, N_BALANCES},
+};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Max number of expired daily snapshots to delete per update
+const MAX_PRUNE_PER_UPDATE: usize = 3;
+
+/// Day index of the given env's block time, used as the history map key
+pub fn day_index(env: &Env) -> u64 {
+    env.block.time.seconds() / SECONDS_PER_DAY
+}
+
+/// Lazily update the current day's snapshot, overwriting its holder count and
+/// supply with their committed values. The `transfer_amount` is Some only for
+/// transfers.
+pub fn update_history(
+    store: &mut dyn Storage,
+    env: &Env,
+    transfer_amount: Option<Uint128>,
+) -> Result<(), ContractError> {
+    let day = day_index(env);
+    let prev_snapshot = HISTORY.may_load(store, day)?;
+    let is_new_day = prev_snapshot.is_none();
+
+    let total_supply = TOKEN_INFO.load(store)?.total_supply;
+
+    let mut snapshot = prev_snapshot.unwrap_or(HistorySnapshot {
+        time: env.block.time,
+        n_balances: Uint64::zero(),
+        total_supply,
+        transfer_count: Uint64::zero(),
+        transfer_volume: Uint128::zero(),
+    });
+
+    snapshot.time = env.block.time;
+    snapshot.n_balances = N_BALANCES.may_load(store)?.unwrap_or_default();
+    snapshot.total_supply = total_supply;
+
+    if let Some(amount) = transfer_amount {
+        snapshot.transfer_count = add_u64(snapshot.transfer_count, 1u64)?;
+        snapshot.transfer_volume = add_u128(snapshot.transfer_volume, amount)?;
+    }
+
+    HISTORY.save(store, day, &snapshot)?;
+
+    // Drop snapshots that have fallen out of the retention window
+    if is_new_day && day >= MAX_HISTORY_DAYS {
+        let expired_days: Vec<u64> = HISTORY
+            .keys(
+                store,
+                None,
+                Some(Bound::Exclusive((day - MAX_HISTORY_DAYS, PhantomData))),
+                Order::Ascending,
+            )
+            .take(MAX_PRUNE_PER_UPDATE)
+            .collect::<Result<Vec<_>, _>>()?;
+        for expired_day in expired_days {
+            HISTORY.remove(store, expired_day);
+        }
+    }
+
+    Ok(())
+}
@@ -1,12 +1,24 @@
+pub mod activity;
 pub mod checks;
 #[cfg(not(feature = "library"))]
 pub mod contract;
+pub mod copy;
 pub mod error;
 #[cfg(not(feature = "library"))]
 pub mod execute;
+pub mod export;
+pub mod history;
+pub mod listeners;
 pub mod math;
+#[cfg(not(feature = "library"))]
+pub mod migrate;
 pub mod msg;
 #[cfg(not(feature = "library"))]
 pub mod query;
+#[cfg(not(feature = "library"))]
+pub mod reply;
 pub mod state;
+pub mod stats;
 pub mod tf;
+pub mod tiers;
+pub mod transfer_history;
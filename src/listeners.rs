@@ -0,0 +1,309 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{to_json_binary, Addr, Env, Order, Storage, SubMsg, WasmMsg};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    math::add_u32,
+    msg::{
+        BalanceChangeEventV2, BalanceChangeEventVersion, BalanceChangeListenerInterface, ListenerHealth,
+        ListenerRegistration, QueuedBalanceChangeEvent,
+    },
+    state::{
+        BALANCE_CHANGE_LISTENERS, BALANCE_CHANGE_LISTENER_CONFIGS, DEFAULT_LISTENER_GAS_LIMIT,
+        DEFAULT_MAX_LISTENER_FAILURES, LEGACY_BALANCE_CHANGE_LISTENERS, LISTENER_DELIVERIES, LISTENER_HEALTH,
+        LISTENER_REPLY_ID_COUNTER, LISTENER_RETRY_QUEUE, LISTENER_RETRY_SEQS, MAX_LISTENER_RETRY_QUEUE_SIZE,
+        MAX_SUBSCRIBER_GAS_LIMIT, SUBSCRIPTIONS, WATCHERS,
+    },
+};
+
+/// Reply IDs at or above this offset belong to balance change listener
+/// submsgs. Each delivery gets its own ID within this range.
+pub const LISTENER_REPLY_ID_OFFSET: u64 = 2_000_000u64;
+/// Number of listener reply IDs, which are reused in turn. This is far more
+/// than the submsgs a single tx can send, so an ID is never reused while
+/// its previous submsg is still in flight.
+pub const LISTENER_REPLY_ID_RANGE: u64 = 100_000u64;
+
+pub fn load_listener_health(
+    store: &dyn Storage,
+    listener: &Addr,
+) -> Result<ListenerHealth, ContractError> {
+    Ok(LISTENER_HEALTH.may_load(store, listener)?.unwrap_or_default())
+}
+
+/// Record a call made to a listener. Successes aren't recorded separately, as
+/// only failed calls reply; they're the calls that haven't failed.
+pub fn record_listener_delivery(
+    store: &mut dyn Storage,
+    listener: &Addr,
+    height: u64,
+) -> Result<ListenerHealth, ContractError> {
+    let mut health = load_listener_health(store, listener)?;
+    health.total_deliveries = add_u32(health.total_deliveries, 1)?;
+    health.last_delivery_height = Some(height);
+    LISTENER_HEALTH.save(store, listener, &health)?;
+    Ok(health)
+}
+
+/// Record a failed delivery to a listener, disabling it once it has failed
+/// too many times in a row. The failure is consecutive to the previous one if
+/// it's for the very next call, as every call in between must have succeeded.
+pub fn record_listener_failure(
+    store: &mut dyn Storage,
+    listener: &Addr,
+    delivery: u32,
+    height: u64,
+    error: String,
+) -> Result<ListenerHealth, ContractError> {
+    let max_failures = BALANCE_CHANGE_LISTENER_CONFIGS
+        .may_load(store, listener)?
+        .and_then(|c| c.max_failures)
+        .unwrap_or(DEFAULT_MAX_LISTENER_FAILURES);
+
+    let mut health = load_listener_health(store, listener)?;
+    health.n_failures = match health.last_failed_delivery {
+        Some(prev) if prev.checked_add(1) == Some(delivery) => add_u32(health.n_failures, 1)?,
+        _ => 1,
+    };
+    health.last_failed_delivery = Some(delivery);
+    health.total_failures = add_u32(health.total_failures, 1)?;
+    health.last_error = Some(error);
+    health.last_failure_height = Some(height);
+    if health.n_failures >= max_failures {
+        health.disabled = true;
+    }
+
+    LISTENER_HEALTH.save(store, listener, &health)?;
+    Ok(health)
+}
+
+/// Append an undelivered event to the back of the listener's retry queue,
+/// evicting the oldest queued event if the queue is full.
+pub fn enqueue_listener_retry(
+    store: &mut dyn Storage,
+    listener: &Addr,
+    queued: &QueuedBalanceChangeEvent,
+) -> Result<(), ContractError> {
+    let seq = LISTENER_RETRY_SEQS.may_load(store, listener)?.unwrap_or_default();
+    LISTENER_RETRY_QUEUE.save(store, (listener, seq), queued)?;
+    LISTENER_RETRY_SEQS.save(store, listener, &(seq + 1))?;
+    if seq >= MAX_LISTENER_RETRY_QUEUE_SIZE {
+        LISTENER_RETRY_QUEUE.remove(store, (listener, seq - MAX_LISTENER_RETRY_QUEUE_SIZE));
+    }
+    Ok(())
+}
+
+/// Discard the listener's retry queue, along with its sequence number
+pub fn clear_listener_retries(
+    store: &mut dyn Storage,
+    listener: &Addr,
+) -> Result<(), ContractError> {
+    let seqs = LISTENER_RETRY_QUEUE
+        .prefix(listener)
+        .keys(store, None, None, Order::Ascending)
+        .collect::<Result<Vec<_>, _>>()?;
+    for seq in seqs {
+        LISTENER_RETRY_QUEUE.remove(store, (listener, seq));
+    }
+    LISTENER_RETRY_SEQS.remove(store, listener);
+    Ok(())
+}
+
+/// Remove and return up to `limit` events from the front of the listener's
+/// retry queue.
+pub fn dequeue_listener_retries(
+    store: &mut dyn Storage,
+    listener: &Addr,
+    limit: usize,
+) -> Result<Vec<QueuedBalanceChangeEvent>, ContractError> {
+    let next_seq = LISTENER_RETRY_SEQS.may_load(store, listener)?.unwrap_or_default();
+    let min_seq = next_seq.saturating_sub(MAX_LISTENER_RETRY_QUEUE_SIZE);
+    let entries = LISTENER_RETRY_QUEUE
+        .prefix(listener)
+        .range(
+            store,
+            Some(Bound::Inclusive((min_seq, PhantomData))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut events = Vec::with_capacity(entries.len());
+    for (seq, queued) in entries {
+        LISTENER_RETRY_QUEUE.remove(store, (listener, seq));
+        events.push(queued);
+    }
+    Ok(events)
+}
+
+/// Move listeners stored in the legacy Vec-based registry into the keyed
+/// registry, registering them at the current block.
+pub fn migrate_balance_change_listeners(
+    store: &mut dyn Storage,
+    env: &Env,
+) -> Result<(), ContractError> {
+    if let Some(addrs) = LEGACY_BALANCE_CHANGE_LISTENERS.may_load(store)? {
+        for addr in addrs.iter() {
+            if !BALANCE_CHANGE_LISTENERS.has(store, addr) {
+                BALANCE_CHANGE_LISTENERS.save(
+                    store,
+                    addr,
+                    &ListenerRegistration {
+                        height: env.block.height,
+                        time: env.block.time,
+                    },
+                )?;
+            }
+        }
+        LEGACY_BALANCE_CHANGE_LISTENERS.remove(store);
+    }
+    Ok(())
+}
+
+/// Event format the listener or subscriber has opted into
+fn load_event_version(
+    store: &dyn Storage,
+    listener: &Addr,
+) -> Result<BalanceChangeEventVersion, ContractError> {
+    let version = match BALANCE_CHANGE_LISTENER_CONFIGS.may_load(store, listener)? {
+        Some(config) => config.event_version,
+        None => SUBSCRIPTIONS.may_load(store, listener)?.and_then(|s| s.event_version),
+    };
+    Ok(version.unwrap_or_default())
+}
+
+/// Build a submsg that calls the listener with the given event. The submsg
+/// only replies on error, so the payload is kept under its reply ID in case
+/// the call fails and the event needs to be moved to the retry queue.
+pub fn build_listener_submsg(
+    store: &mut dyn Storage,
+    listener: &Addr,
+    queued: QueuedBalanceChangeEvent,
+    height: u64,
+) -> Result<SubMsg, ContractError> {
+    let queued = QueuedBalanceChangeEvent {
+        n_attempts: add_u32(queued.n_attempts, 1)?,
+        ..queued
+    };
+
+    let counter = LISTENER_REPLY_ID_COUNTER
+        .may_load(store)?
+        .unwrap_or(LISTENER_REPLY_ID_OFFSET);
+    LISTENER_REPLY_ID_COUNTER.save(store, &(counter + 1))?;
+    let reply_id = LISTENER_REPLY_ID_OFFSET + (counter - LISTENER_REPLY_ID_OFFSET) % LISTENER_REPLY_ID_RANGE;
+
+    let msg = match load_event_version(store, listener)? {
+        BalanceChangeEventVersion::V1 => BalanceChangeListenerInterface::OnBalanceChange {
+            event: queued.event.event.to_owned(),
+        },
+        BalanceChangeEventVersion::V2 => BalanceChangeListenerInterface::OnBalanceChangeV2 {
+            event: queued.event.to_owned(),
+        },
+    };
+
+    let submsg = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: listener.to_string(),
+            funds: vec![],
+            msg: to_json_binary(&msg)?,
+        },
+        reply_id,
+    );
+
+    let delivery = record_listener_delivery(store, listener, height)?.total_deliveries;
+    LISTENER_DELIVERIES.save(store, reply_id, &(listener.to_owned(), delivery, queued))?;
+
+    // Every call is limited, so that one misbehaving listener can't use up
+    // the gas of the transaction that triggered it. Registered listeners
+    // default to DEFAULT_LISTENER_GAS_LIMIT unless their config sets a limit.
+    let gas_limit = match BALANCE_CHANGE_LISTENER_CONFIGS.may_load(store, listener)? {
+        Some(config) => config.gas_limit.unwrap_or(DEFAULT_LISTENER_GAS_LIMIT),
+        None if BALANCE_CHANGE_LISTENERS.has(store, listener) => DEFAULT_LISTENER_GAS_LIMIT,
+        None => SUBSCRIPTIONS
+            .may_load(store, listener)?
+            .and_then(|s| s.gas_limit)
+            .unwrap_or(MAX_SUBSCRIBER_GAS_LIMIT),
+    };
+
+    Ok(submsg.with_gas_limit(gas_limit))
+}
+
+/// Execute callback on self-service subscribers watching any account affected
+/// by the event. Subscribers that are also registered listeners are skipped, as
+/// are subscribers disabled after repeated failures. Subscribers only receive
+/// the default event kinds.
+fn notify_subscribers(
+    store: &mut dyn Storage,
+    event: &BalanceChangeEventV2,
+) -> Result<Vec<SubMsg>, ContractError> {
+    if !event.event.kind().is_default() {
+        return Ok(vec![]);
+    }
+
+    let mut subscribers: Vec<Addr> = vec![];
+    for account in event.event.accounts() {
+        for result in WATCHERS.prefix(account).keys(store, None, None, Order::Ascending) {
+            let subscriber = result?;
+            if !subscribers.contains(&subscriber) && !BALANCE_CHANGE_LISTENERS.has(store, &subscriber) {
+                subscribers.push(subscriber);
+            }
+        }
+    }
+
+    let mut submsgs = Vec::with_capacity(subscribers.len());
+    for subscriber in subscribers.iter() {
+        if load_listener_health(store, subscriber)?.disabled {
+            continue;
+        }
+        submsgs.push(build_listener_submsg(
+            store,
+            subscriber,
+            QueuedBalanceChangeEvent {
+                event: event.to_owned(),
+                n_attempts: 0,
+            },
+            event.height,
+        )?);
+    }
+    Ok(submsgs)
+}
+
+// Execute callback on balance change listener contracts. Each listener is
+// called through its own submsg so that a failing listener can't revert the
+// balance change itself.
+pub fn notify_balance_change_listeners(
+    store: &mut dyn Storage,
+    event: &BalanceChangeEventV2,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let mut submsgs = Vec::with_capacity(1);
+    let listener_addrs = BALANCE_CHANGE_LISTENERS
+        .keys(store, None, None, Order::Ascending)
+        .collect::<Result<Vec<_>, _>>()?;
+    for contract_addr in listener_addrs.iter() {
+        // Skip listeners whose filter excludes this event
+        let filter = BALANCE_CHANGE_LISTENER_CONFIGS
+            .may_load(store, contract_addr)?
+            .and_then(|c| c.filter)
+            .unwrap_or_default();
+        if !filter.matches(&event.event) {
+            continue;
+        }
+
+        // Disabled listeners miss events until they're re-enabled, apart from
+        // those already in their retry queue
+        if load_listener_health(store, contract_addr)?.disabled {
+            continue;
+        }
+
+        let queued = QueuedBalanceChangeEvent {
+            event: event.to_owned(),
+            n_attempts: 0,
+        };
+        submsgs.push(build_listener_submsg(store, contract_addr, queued, event.height)?);
+    }
+    submsgs.extend(notify_subscribers(store, event)?);
+    Ok(submsgs)
+}
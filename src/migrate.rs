@@ -0,0 +1,101 @@
+use cosmwasm_std::{attr, DepsMut, Env, Response, Storage, Uint64};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
+use crate::{
+    contract::{CONTRACT_NAME, CONTRACT_VERSION},
+    error::ContractError,
+    execute::operator::{
+        reindex::start_reindex, set_operator::exec_set_operator,
+        update_balance_change_listeners::exec_update_balance_change_listeners,
+    },
+    listeners::migrate_balance_change_listeners,
+    msg::MigrateMsg,
+    state::{tf::TF_N_BALANCES_INITIALIZED, GLOBAL_BALANCE_FREEZE, REINDEX_STATE},
+};
+
+type Upgrade = fn(&mut dyn Storage, &Env) -> Result<(), ContractError>;
+
+/// State upgrades in ascending order of version. Each runs when migrating from
+/// a version below its own.
+const UPGRADES: &[(&str, Upgrade)] = &[("0.1.0", upgrade_v0_1_0)];
+
+/// Migrate from an earlier version of this contract, refusing other contracts
+/// and downgrades, then apply the state upgrades and options in the message.
+pub fn exec_migrate(
+    mut deps: DepsMut,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot migrate from {}", stored.contract),
+        });
+    }
+
+    let prev_version = parse_version(&stored.version)?;
+    let next_version = parse_version(CONTRACT_VERSION)?;
+    if prev_version > next_version {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot downgrade from {} to {}", prev_version, next_version),
+        });
+    }
+
+    let mut upgrades: Vec<&str> = vec![];
+    for (version, upgrade) in UPGRADES.iter() {
+        if prev_version < parse_version(version)? {
+            upgrade(deps.storage, &env)?;
+            upgrades.push(version);
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if let Some(operator) = msg.operator {
+        exec_set_operator(deps.branch(), operator)?;
+    }
+
+    if let Some(listeners) = msg.listeners {
+        exec_update_balance_change_listeners(deps.branch(), env.to_owned(), None, None, Some(listeners))?;
+    }
+
+    // Restart any Reindex already in progress from the beginning
+    let reindex = msg.reindex.unwrap_or(false);
+    if reindex {
+        start_reindex(deps.storage)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("prev_version", prev_version.to_string()),
+        attr("version", next_version.to_string()),
+        attr("upgrades", upgrades.join(",")),
+        attr("reindex", REINDEX_STATE.exists(deps.storage).to_string()),
+    ]))
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|e| ContractError::ValidationError {
+        reason: format!("invalid contract version {}: {}", version, e),
+    })
+}
+
+/// Move listeners into the keyed registry and initialize storage items that
+/// deployments from before 0.1.0 may be missing. Their balance stats and tier
+/// counts were never tracked, so a Reindex is started to build them. Until the
+/// operator completes it, balance changes leave the stats alone.
+fn upgrade_v0_1_0(
+    store: &mut dyn Storage,
+    env: &Env,
+) -> Result<(), ContractError> {
+    migrate_balance_change_listeners(store, env)?;
+    if !GLOBAL_BALANCE_FREEZE.exists(store) {
+        GLOBAL_BALANCE_FREEZE.save(store, &false)?;
+    }
+    if !TF_N_BALANCES_INITIALIZED.exists(store) {
+        TF_N_BALANCES_INITIALIZED.save(store, &Uint64::zero())?;
+    }
+    start_reindex(store)?;
+    Ok(())
+}
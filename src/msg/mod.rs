@@ -1,8 +1,9 @@
 pub mod tf;
 
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Binary, Uint128};
-use cw20::{Expiration, Logo};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Env, HexBinary, Int256, Timestamp, Uint128, Uint64};
+use cw20::{AllowanceResponse, Cw20ReceiveMsg, Expiration, Logo, MarketingInfoResponse};
+use cw20_base::state::TokenInfo;
 
 #[cw_serde]
 pub enum OperatorExecuteMsg {
@@ -16,13 +17,79 @@ pub enum OperatorExecuteMsg {
     UnfreezeBalances {
         addresses: Option<Vec<Addr>>,
     },
+    /// Copy balances from another CW20 or a bank denom in batches, adjusting
+    /// total supply by the net change in balances
     CopyBalances {
-        cw20_address: Addr,
+        source: CopySource,
         mode: BalanceCopyMode,
+        /// What to do when a copied balance would push total supply past the
+        /// minter cap. Defaults to Fail, or to the cap behavior the copy in
+        /// progress was started with, which can't be changed.
+        cap_behavior: Option<CopyCapBehavior>,
+        /// Re-encode source addresses with this chain's bech32 prefix, for
+        /// copying from a CW20 on another chain. Addresses that can't be
+        /// converted are skipped. Defaults to false, or to the setting of the
+        /// copy in progress, which can't be changed.
+        remap_prefix: Option<bool>,
     },
+    /// Abandon the copy in progress. Balances copied so far are kept.
+    CancelCopy {},
+    /// Rebuild the ordered balance index, holder count, stats and tier counts
+    /// from BALANCES, processing up to `limit` entries per call. Repeat until
+    /// the response's reindex event reports done.
+    Reindex {
+        limit: Option<u32>,
+    },
+    /// Restore a page of records from another contract's Export, in order,
+    /// into this freshly created contract. The checksum is the one returned
+    /// with the page and must match the running checksum of all records
+    /// imported so far. Set done on the last page to verify total supply and
+    /// hand over to the exported operator. The deposits of the page's
+    /// subscriptions must be attached, so that they can still be refunded.
+    Import {
+        records: Vec<ExportRecord>,
+        checksum: ExportChecksum,
+        done: bool,
+    },
+    /// Register or unregister balance change listener contracts. Listeners in
+    /// `add` are registered without changing any existing config, while each
+    /// entry in `configs` registers its listener if needed and replaces its
+    /// filter and delivery settings.
     UpdateBalanceChangeListeners {
         add: Option<Vec<Addr>>,
         remove: Option<Vec<Addr>>,
+        configs: Option<Vec<BalanceChangeListenerConfig>>,
+    },
+    /// Re-enable a listener or subscriber that was disabled after repeated
+    /// failures
+    EnableBalanceChangeListener {
+        address: Addr,
+    },
+    /// Set or clear the deposit required to create a new subscription
+    SetSubscriptionDeposit {
+        deposit: Option<Coin>,
+    },
+    /// Add or remove accounts from the registry of accounts that don't count
+    /// toward circulating supply, holder counts and ranked balance queries.
+    UpdateExcludedAccounts {
+        add: Option<Vec<Addr>>,
+        remove: Option<Vec<Addr>>,
+    },
+    /// Add or remove legacy CW20s whose holders can swap them for this token
+    /// by sending them here. Adding an existing token replaces its settings.
+    UpdateLegacyTokens {
+        add: Option<Vec<LegacyToken>>,
+        remove: Option<Vec<Addr>>,
+    },
+    /// Replace the holder tier thresholds. Existing holders are recounted by a
+    /// Reindex, which the operator must then run to completion.
+    SetTiers {
+        tiers: Vec<HolderTier>,
+    },
+    /// Set the number of recent transfers kept per account for the
+    /// TransferHistory query. Zero, the default, turns recording off.
+    SetTransferHistorySize {
+        size: u16,
     },
 }
 
@@ -60,10 +127,39 @@ pub enum ExecuteMsg {
     Pro(OperatorExecuteMsg),
     /// Tokenfactory-related functions
     TokenFactory(TokenFactoryExecuteMsg),
+    /// Redeliver events that previously failed to reach a balance change
+    /// listener. Anyone can call this.
+    RetryBalanceChangeNotifications { listener: Addr, limit: Option<u16> },
+    /// Subscribe the sending contract to balance change events that affect
+    /// any of the watched addresses. Replaces any existing subscription. Each
+    /// callback is limited to gas_limit, which can't exceed
+    /// MAX_SUBSCRIBER_GAS_LIMIT.
+    Subscribe {
+        watched: Vec<Addr>,
+        event_version: Option<BalanceChangeEventVersion>,
+        gas_limit: u64,
+    },
+    /// Cancel the sender's subscription and refund its deposit
+    Unsubscribe {},
+    /// Stop a subscriber from watching the sender's address, and from watching
+    /// it again until allowed. The operator can instead cancel the whole
+    /// subscription, refunding its deposit.
+    EvictSubscriber { subscriber: Addr },
+    /// Let a subscriber evicted from watching the sender's address watch it
+    /// again
+    AllowSubscriber { subscriber: Addr },
+    /// Swap legacy CW20 tokens sent with Send for newly minted tokens. The
+    /// sending token must be in the legacy token allowlist.
+    Receive(Cw20ReceiveMsg),
 
     /// Implements CW20. Transfer is a base message to move tokens to another
-    /// account without triggering actions
-    Transfer { recipient: String, amount: Uint128 },
+    /// account without triggering actions. The optional memo is kept in
+    /// transfer history, if enabled.
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+        memo: Option<String>,
+    },
     /// Implements CW20. Only with the "mintable" extension. If authorized,
     /// creates amount new tokens and adds to the recipient balance.
     Mint { recipient: String, amount: Uint128 },
@@ -79,6 +175,7 @@ pub enum ExecuteMsg {
         contract: String,
         amount: Uint128,
         msg: Binary,
+        memo: Option<String>,
     },
     /// Implements CW20 "approval" extension. Allows spender to access an
     /// additional amount tokens from the owner's (env.sender) account. If
@@ -103,6 +200,7 @@ pub enum ExecuteMsg {
         owner: String,
         recipient: String,
         amount: Uint128,
+        memo: Option<String>,
     },
     /// Implements CW20 "approval" extension. Sends amount tokens from owner ->
     /// contract if `env.sender` has sufficient pre-approval.
@@ -111,6 +209,7 @@ pub enum ExecuteMsg {
         contract: String,
         amount: Uint128,
         msg: Binary,
+        memo: Option<String>,
     },
     /// Implements CW20 "approval" extension. Destroys tokens forever
     BurnFrom { owner: String, amount: Uint128 },
@@ -128,10 +227,24 @@ pub enum ExecuteMsg {
 
 #[cw_serde]
 pub enum ProBalanceQueryMsg {
+    /// Paginate balances in order of amount. Optionally restrict results to
+    /// balances within an inclusive amount range and skip any addresses in
+    /// the exclude list, like treasury or LP pool accounts. Accounts in the
+    /// excluded-account registry are skipped unless include_excluded is set.
+    /// Unset filters are left out when serialized, so that the query is still
+    /// understood by cw20-pro contracts deployed before they were added.
     All {
         limit: Option<u16>,
         desc: Option<bool>,
         cursor: Option<(Uint128, Addr)>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_amount: Option<Uint128>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_amount: Option<Uint128>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exclude: Option<Vec<Addr>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        include_excluded: Option<bool>,
     },
     ByAddress {
         addresses: Vec<Addr>,
@@ -144,9 +257,199 @@ pub enum BalanceCopyMode {
     Increment,
 }
 
+#[cw_serde]
+pub enum CopySource {
+    /// A CW20 contract. Other cw20-pro contracts are detected automatically.
+    Cw20 { address: Addr },
+    /// Holders of a native bank denom. If the exponent of the denom's display
+    /// unit is given, amounts are converted from it to this token's decimals.
+    Denom { denom: String, exponent: Option<u8> },
+}
+
+impl CopySource {
+    /// Key of the source in BALANCE_COPY_CURSORS: the CW20 address, or the
+    /// denom prefixed with "denom:", which no address can start with
+    pub fn key(&self) -> String {
+        match self {
+            CopySource::Cw20 { address } => address.to_string(),
+            CopySource::Denom { denom, .. } => format!("denom:{}", denom),
+        }
+    }
+}
+
+/// How balances are read from a copy source
+#[cw_serde]
+pub enum CopySourceKind {
+    /// Plain CW20, read one Balance query per account
+    Cw20,
+    /// Another cw20-pro contract, read in pages of addresses and amounts
+    Cw20Pro,
+    /// Bank denom, read in pages with the DenomOwners query
+    Denom,
+}
+
+/// Progress of a balance copy spanning multiple CopyBalances calls
+#[cw_serde]
+pub struct BalanceCopyJob {
+    pub source: CopySource,
+    pub source_kind: CopySourceKind,
+    pub mode: BalanceCopyMode,
+    pub cap_behavior: CopyCapBehavior,
+    #[serde(default)]
+    pub remap_prefix: bool,
+    pub n_accounts: u64,
+    /// Source accounts skipped because their address couldn't be remapped
+    #[serde(default)]
+    pub n_skipped: u64,
+    pub amount_added: Uint128,
+    pub amount_removed: Uint128,
+}
+
+#[cw_serde]
+pub struct CopyJobResponse {
+    /// Key of the source being copied: the CW20 address, or the denom
+    /// prefixed with "denom:"
+    pub source: Option<String>,
+    /// Last source account copied
+    pub cursor: Option<String>,
+    /// Job progress, if tracked. Copies started before job tracking only
+    /// have a source and cursor.
+    pub job: Option<BalanceCopyJob>,
+}
+
+#[cw_serde]
+pub struct CopyBalanceChange {
+    pub address: Addr,
+    pub prev_balance: Uint128,
+    pub next_balance: Uint128,
+}
+
+#[cw_serde]
+pub struct CopyPreviewResponse {
+    pub source_kind: CopySourceKind,
+    pub changes: Vec<CopyBalanceChange>,
+    /// Total supply once the changes are applied
+    pub total_supply: Uint128,
+    /// Source addresses that would be skipped when remapping prefixes
+    pub skipped: Vec<String>,
+    pub cursor: Option<String>,
+    pub done: bool,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum CopyCapBehavior {
+    /// Abort the copy
+    #[default]
+    Fail,
+    /// Copy only as much of the balance as the cap allows
+    Truncate,
+}
+
 #[cw_serde]
 pub enum ProQueryMsg {
     Balances(ProBalanceQueryMsg),
+    /// Total supply minus the balances of all registered excluded accounts.
+    /// Register vesting contracts as excluded to leave out locked vesting.
+    CirculatingSupply {},
+    /// Number of non-zero balances, not counting excluded accounts
+    HolderCount {},
+    /// Paginate the excluded-account registry along with current balances
+    ExcludedAccounts {
+        limit: Option<u16>,
+        cursor: Option<Addr>,
+    },
+    /// Legacy CW20s accepted through Receive, with their swap settings
+    LegacyTokens {},
+    /// Configured holder tiers along with the number of holders in each, not
+    /// counting excluded accounts unless include_excluded is set
+    Tiers {
+        include_excluded: Option<bool>,
+    },
+    /// Return the tier of the given account, if any
+    Tier {
+        address: Addr,
+    },
+    /// Paginate the balances of holders in the named tier, skipping excluded
+    /// accounts unless include_excluded is set
+    TierHolders {
+        tier: String,
+        limit: Option<u16>,
+        desc: Option<bool>,
+        cursor: Option<(Uint128, Addr)>,
+        include_excluded: Option<bool>,
+    },
+    /// Holder count, top-N supply shares, HHI concentration index and a
+    /// log10-scale balance histogram. Accounts in the excluded-account
+    /// registry are left out, with shares measured against circulating
+    /// supply, unless include_excluded is set.
+    Stats {
+        include_excluded: Option<bool>,
+    },
+    /// Paginate daily holder count and supply snapshots between two times
+    History {
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+        limit: Option<u16>,
+    },
+    /// Lifetime amounts sent, received and burned by an account
+    AccountStats {
+        address: Addr,
+    },
+    /// Paginate accounts in descending order of transfers sent
+    TopSenders {
+        limit: Option<u16>,
+        cursor: Option<(Uint64, Addr)>,
+    },
+    /// Paginate an account's most recent transfers, newest first
+    TransferHistory {
+        address: Addr,
+        limit: Option<u16>,
+        cursor: Option<u32>,
+    },
+    /// Delivery failure stats of each balance change listener
+    ListenerHealth {},
+    /// Paginate registered listeners with their filters, registration block
+    /// and delivery stats
+    Listeners {
+        limit: Option<u16>,
+        cursor: Option<Addr>,
+    },
+    /// Paginate events waiting to be redelivered to a listener, oldest first
+    ListenerQueue {
+        listener: Addr,
+        limit: Option<u16>,
+        cursor: Option<u64>,
+    },
+    /// Return the given contract's subscription, if any
+    Subscription {
+        subscriber: Addr,
+    },
+    /// Check a page of contract state for consistency between BALANCES,
+    /// ORDERED_BALANCES, N_BALANCES and total supply. Pass the returned cursor
+    /// back in to continue, until done is true.
+    Audit {
+        limit: Option<u16>,
+        cursor: Option<AuditCursor>,
+    },
+    /// Serialize a page of contract state for restoring into a new contract
+    /// with Import. Pass the returned cursor back in to continue, until done
+    /// is true. The contract should be frozen while exporting.
+    Export {
+        limit: Option<u16>,
+        cursor: Option<ExportCursor>,
+    },
+    /// Return the balance copy in progress, if any
+    CopyJob {},
+    /// Preview the balance changes the next CopyBalances call would make,
+    /// without writing anything. Arguments are checked against the copy in
+    /// progress as CopyBalances would.
+    CopyPreview {
+        source: CopySource,
+        mode: BalanceCopyMode,
+        cap_behavior: Option<CopyCapBehavior>,
+        remap_prefix: Option<bool>,
+    },
 }
 
 #[cw_serde]
@@ -203,7 +506,16 @@ pub enum QueryMsg {
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Replace the operator
+    pub operator: Option<Addr>,
+    /// Register or update balance change listeners
+    pub listeners: Option<Vec<BalanceChangeListenerConfig>>,
+    /// Start rebuilding the ordered balance index, to be continued with the
+    /// operator's Reindex. Ranked queries are blocked until it completes.
+    /// Migrating from a version before 0.1.0 always starts one.
+    pub reindex: Option<bool>,
+}
 
 #[cw_serde]
 pub struct AccountBalance {
@@ -217,6 +529,148 @@ pub struct BalancesResponse {
     pub cursor: Option<(Uint128, Addr)>,
 }
 
+#[cw_serde]
+pub struct CirculatingSupplyResponse {
+    pub total_supply: Uint128,
+    pub excluded_supply: Uint128,
+    pub circulating_supply: Uint128,
+}
+
+#[cw_serde]
+pub struct HolderCountResponse {
+    pub n_holders: Uint64,
+    pub n_excluded: Uint64,
+}
+
+#[cw_serde]
+pub struct ExcludedAccountsResponse {
+    pub accounts: Vec<AccountBalance>,
+    pub cursor: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct HolderTier {
+    pub name: String,
+    pub min_balance: Uint128,
+}
+
+#[cw_serde]
+pub struct TierStats {
+    pub tier: HolderTier,
+    pub n_holders: Uint64,
+}
+
+#[cw_serde]
+pub struct TiersResponse {
+    pub tiers: Vec<TierStats>,
+}
+
+#[cw_serde]
+pub struct AccountTierResponse {
+    pub address: Addr,
+    pub balance: Uint128,
+    pub tier: Option<HolderTier>,
+}
+
+#[cw_serde]
+pub struct HistogramBucket {
+    /// Inclusive lower bound of the bucket. The upper bound is 10x this.
+    pub min_balance: Uint128,
+    pub n_holders: Uint64,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub n_holders: Uint64,
+    /// Supply that the shares and HHI are measured against: circulating
+    /// supply, or total supply if excluded accounts are included
+    pub total_supply: Uint128,
+    pub top_10_share: Decimal,
+    pub top_100_share: Decimal,
+    /// Herfindahl-Hirschman index, between 0 and 1
+    pub hhi: Decimal,
+    pub histogram: Vec<HistogramBucket>,
+}
+
+#[cw_serde]
+pub enum ReindexPhase {
+    /// Removing entries from the existing ordered balance index
+    Clearing,
+    /// Re-adding every non-zero balance to the index
+    Indexing,
+}
+
+#[cw_serde]
+pub struct ReindexState {
+    pub phase: ReindexPhase,
+    /// Last account indexed in the Indexing phase
+    pub cursor: Option<Addr>,
+    pub n_cleared: u64,
+    pub n_indexed: u64,
+}
+
+#[cw_serde]
+pub struct HistorySnapshot {
+    /// Time of the last update to this day's snapshot
+    pub time: Timestamp,
+    pub n_balances: Uint64,
+    pub total_supply: Uint128,
+    pub transfer_count: Uint64,
+    pub transfer_volume: Uint128,
+}
+
+#[cw_serde]
+pub struct HistoryResponse {
+    pub snapshots: Vec<HistorySnapshot>,
+    pub cursor: Option<Timestamp>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct AccountStats {
+    pub amount_sent: Uint128,
+    /// Amount received through transfers and mints
+    pub amount_received: Uint128,
+    pub amount_burned: Uint128,
+    pub n_sent: Uint64,
+    pub n_received: Uint64,
+}
+
+#[cw_serde]
+pub struct AccountStatsResponse {
+    pub address: Addr,
+    pub stats: AccountStats,
+}
+
+#[cw_serde]
+pub struct AccountActivity {
+    pub address: Addr,
+    pub stats: AccountStats,
+}
+
+#[cw_serde]
+pub struct TopSendersResponse {
+    pub senders: Vec<AccountActivity>,
+    pub cursor: Option<(Uint64, Addr)>,
+}
+
+#[cw_serde]
+pub struct TransferRecord {
+    pub counterparty: Addr,
+    /// Negative for outgoing transfers and positive for incoming ones
+    pub amount: Int256,
+    pub height: u64,
+    pub time: Timestamp,
+    pub memo: Option<String>,
+}
+
+#[cw_serde]
+pub struct TransferHistoryResponse {
+    pub transfers: Vec<TransferRecord>,
+    pub cursor: Option<u32>,
+}
+
 #[cw_serde]
 pub enum BalanceChangeEvent {
     Transfer {
@@ -237,9 +691,544 @@ pub enum BalanceChangeEvent {
         recipient_balance: Uint128,
         amount: Uint128,
     },
+    TierChanged {
+        address: Addr,
+        balance: Uint128,
+        prev_tier: Option<String>,
+        tier: Option<String>,
+    },
+    AllowanceIncreased {
+        owner: Addr,
+        spender: Addr,
+        amount: Uint128,
+        allowance: Uint128,
+    },
+    AllowanceDecreased {
+        owner: Addr,
+        spender: Addr,
+        amount: Uint128,
+        allowance: Uint128,
+    },
+    /// A single account or, if address is None, all balances were frozen
+    Frozen {
+        address: Option<Addr>,
+    },
+    /// A single account or, if address is None, all balances were unfrozen
+    Unfrozen {
+        address: Option<Addr>,
+    },
+    /// An account's balance went from zero to non-zero
+    HolderAdded {
+        address: Addr,
+        balance: Uint128,
+    },
+    /// An account's balance went from non-zero to zero
+    HolderRemoved {
+        address: Addr,
+    },
+}
+
+impl BalanceChangeEvent {
+    pub fn kind(&self) -> BalanceChangeEventKind {
+        match self {
+            BalanceChangeEvent::Transfer { .. } => BalanceChangeEventKind::Transfer,
+            BalanceChangeEvent::Burn { .. } => BalanceChangeEventKind::Burn,
+            BalanceChangeEvent::Mint { .. } => BalanceChangeEventKind::Mint,
+            BalanceChangeEvent::TierChanged { .. } => BalanceChangeEventKind::TierChanged,
+            BalanceChangeEvent::AllowanceIncreased { .. } => BalanceChangeEventKind::AllowanceIncreased,
+            BalanceChangeEvent::AllowanceDecreased { .. } => BalanceChangeEventKind::AllowanceDecreased,
+            BalanceChangeEvent::Frozen { .. } => BalanceChangeEventKind::Frozen,
+            BalanceChangeEvent::Unfrozen { .. } => BalanceChangeEventKind::Unfrozen,
+            BalanceChangeEvent::HolderAdded { .. } => BalanceChangeEventKind::HolderAdded,
+            BalanceChangeEvent::HolderRemoved { .. } => BalanceChangeEventKind::HolderRemoved,
+        }
+    }
+
+    /// Accounts affected by the event. Global freezes and unfreezes affect
+    /// every account and so don't name any.
+    pub fn accounts(&self) -> Vec<&Addr> {
+        match self {
+            BalanceChangeEvent::Transfer { initiator, recipient, .. } => vec![initiator, recipient],
+            BalanceChangeEvent::Burn { initiator, .. } => vec![initiator],
+            BalanceChangeEvent::Mint { recipient, .. } => vec![recipient],
+            BalanceChangeEvent::TierChanged { address, .. }
+            | BalanceChangeEvent::HolderAdded { address, .. }
+            | BalanceChangeEvent::HolderRemoved { address } => vec![address],
+            BalanceChangeEvent::AllowanceIncreased { owner, spender, .. }
+            | BalanceChangeEvent::AllowanceDecreased { owner, spender, .. } => vec![owner, spender],
+            BalanceChangeEvent::Frozen { address } | BalanceChangeEvent::Unfrozen { address } => {
+                address.iter().collect()
+            },
+        }
+    }
+
+    /// Amount of tokens moved, minted, burned or approved, if applicable
+    pub fn amount(&self) -> Option<Uint128> {
+        match self {
+            BalanceChangeEvent::Transfer { amount, .. }
+            | BalanceChangeEvent::Burn { amount, .. }
+            | BalanceChangeEvent::Mint { amount, .. }
+            | BalanceChangeEvent::AllowanceIncreased { amount, .. }
+            | BalanceChangeEvent::AllowanceDecreased { amount, .. } => Some(*amount),
+            BalanceChangeEvent::TierChanged { .. }
+            | BalanceChangeEvent::Frozen { .. }
+            | BalanceChangeEvent::Unfrozen { .. }
+            | BalanceChangeEvent::HolderAdded { .. }
+            | BalanceChangeEvent::HolderRemoved { .. } => None,
+        }
+    }
+
+    /// Return a HolderAdded or HolderRemoved event if the account's balance
+    /// crossed between zero and non-zero.
+    pub fn holder_set_change(
+        address: &Addr,
+        prev_balance: Uint128,
+        next_balance: Uint128,
+    ) -> Option<Self> {
+        match (prev_balance.is_zero(), next_balance.is_zero()) {
+            (true, false) => Some(BalanceChangeEvent::HolderAdded {
+                address: address.to_owned(),
+                balance: next_balance,
+            }),
+            (false, true) => Some(BalanceChangeEvent::HolderRemoved {
+                address: address.to_owned(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The CW20 operation that caused an event
+#[cw_serde]
+pub enum BalanceChangeOperation {
+    Transfer,
+    Send,
+    TransferFrom { spender: Addr },
+    SendFrom { spender: Addr },
+    Mint,
+    Burn,
+    BurnFrom { spender: Addr },
+    CopyBalances { source: CopySource },
+    IncreaseAllowance,
+    DecreaseAllowance,
+    Freeze,
+    Unfreeze,
+}
+
+/// Version 2 of the balance change event format. Wraps the v1 event with block
+/// context, the operation that caused it, balances prior to the change, and
+/// total supply after the change.
+#[cw_serde]
+pub struct BalanceChangeEventV2 {
+    pub event: BalanceChangeEvent,
+    pub operation: BalanceChangeOperation,
+    pub prev_balances: Vec<AccountBalance>,
+    pub total_supply: Uint128,
+    pub height: u64,
+    pub time: Timestamp,
+    pub tx_index: Option<u32>,
+}
+
+impl BalanceChangeEventV2 {
+    pub fn new(
+        env: &Env,
+        event: BalanceChangeEvent,
+        operation: BalanceChangeOperation,
+        prev_balances: Vec<AccountBalance>,
+        total_supply: Uint128,
+    ) -> Self {
+        Self {
+            event,
+            operation,
+            prev_balances,
+            total_supply,
+            height: env.block.height,
+            time: env.block.time,
+            tx_index: env.transaction.as_ref().map(|t| t.index),
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum BalanceChangeEventVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+#[cw_serde]
+pub enum BalanceChangeEventKind {
+    Transfer,
+    Burn,
+    Mint,
+    TierChanged,
+    AllowanceIncreased,
+    AllowanceDecreased,
+    Frozen,
+    Unfrozen,
+    HolderAdded,
+    HolderRemoved,
+}
+
+impl BalanceChangeEventKind {
+    /// Whether the kind is sent to listeners that don't list kinds in their
+    /// filter. Other kinds are opt-in so that existing listeners don't start
+    /// receiving events they can't deserialize.
+    pub fn is_default(&self) -> bool {
+        matches!(
+            self,
+            BalanceChangeEventKind::Transfer
+                | BalanceChangeEventKind::Burn
+                | BalanceChangeEventKind::Mint
+        )
+    }
+}
+
+/// Criteria an event must meet to be sent to a listener. Unset fields match
+/// everything, except that kinds defaults to the default event kinds.
+#[cw_serde]
+#[derive(Default)]
+pub struct BalanceChangeFilter {
+    pub kinds: Option<Vec<BalanceChangeEventKind>>,
+    /// Only send events that affect the balance of one of these accounts
+    pub addresses: Option<Vec<Addr>>,
+    /// Only send events that move at least this amount. Doesn't apply to
+    /// events without an amount, like TierChanged.
+    pub min_amount: Option<Uint128>,
+}
+
+impl BalanceChangeFilter {
+    pub fn matches(
+        &self,
+        event: &BalanceChangeEvent,
+    ) -> bool {
+        match &self.kinds {
+            Some(kinds) if !kinds.contains(&event.kind()) => return false,
+            None if !event.kind().is_default() => return false,
+            _ => {},
+        }
+        if let Some(addresses) = &self.addresses {
+            if !event.accounts().iter().any(|a| addresses.contains(a)) {
+                return false;
+            }
+        }
+        if let (Some(min_amount), Some(amount)) = (self.min_amount, event.amount()) {
+            if amount < min_amount {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cw_serde]
+pub struct BalanceChangeListenerConfig {
+    pub address: Addr,
+    pub filter: Option<BalanceChangeFilter>,
+    /// Gas limit for each call to the listener. Defaults to
+    /// DEFAULT_LISTENER_GAS_LIMIT.
+    pub gas_limit: Option<u64>,
+    /// Consecutive failures after which the listener is disabled
+    pub max_failures: Option<u32>,
+    /// Event format sent to the listener. Defaults to V1.
+    pub event_version: Option<BalanceChangeEventVersion>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ListenerHealth {
+    /// Consecutive failures, with no successful call in between, since the
+    /// listener was last (re-)enabled
+    pub n_failures: u32,
+    pub total_failures: u32,
+    pub last_error: Option<String>,
+    pub last_failure_height: Option<u64>,
+    pub disabled: bool,
+    /// Calls made to the listener, counted when they're sent. Calls that
+    /// failed are also counted in total_failures.
+    #[serde(default)]
+    pub total_deliveries: u32,
+    /// Block of the last call made to the listener
+    pub last_delivery_height: Option<u64>,
+    /// Call number, out of total_deliveries, of the last failed call
+    #[serde(default)]
+    pub last_failed_delivery: Option<u32>,
+}
+
+#[cw_serde]
+pub struct ListenerStatus {
+    pub address: Addr,
+    pub health: ListenerHealth,
+}
+
+#[cw_serde]
+pub struct ListenerHealthResponse {
+    pub listeners: Vec<ListenerStatus>,
+}
+
+#[cw_serde]
+pub struct ListenerRegistration {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+#[cw_serde]
+pub struct ListenerInfo {
+    pub config: BalanceChangeListenerConfig,
+    pub registration: ListenerRegistration,
+    pub health: ListenerHealth,
+    /// Number of events waiting in the listener's retry queue
+    pub n_queued: u64,
+}
+
+#[cw_serde]
+pub struct ListenersResponse {
+    pub listeners: Vec<ListenerInfo>,
+    pub cursor: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct Subscription {
+    pub watched: Vec<Addr>,
+    pub event_version: Option<BalanceChangeEventVersion>,
+    /// Deposit refunded when unsubscribing
+    pub deposit: Option<Coin>,
+    /// Gas limit for each callback. Subscriptions made before it was required
+    /// don't have one and are limited to MAX_SUBSCRIBER_GAS_LIMIT.
+    #[serde(default)]
+    pub gas_limit: Option<u64>,
+}
+
+#[cw_serde]
+pub struct SubscriptionResponse {
+    pub subscriber: Addr,
+    pub subscription: Option<Subscription>,
+    /// Delivery health, which is kept after unsubscribing
+    pub health: ListenerHealth,
+}
+
+/// A balance change event along with its delivery attempts
+#[cw_serde]
+pub struct QueuedBalanceChangeEvent {
+    pub event: BalanceChangeEventV2,
+    /// Number of delivery attempts made so far
+    pub n_attempts: u32,
+}
+
+#[cw_serde]
+pub struct ListenerQueueEntry {
+    pub seq: u64,
+    pub queued: QueuedBalanceChangeEvent,
+}
+
+#[cw_serde]
+pub struct ListenerQueueResponse {
+    pub events: Vec<ListenerQueueEntry>,
+    pub cursor: Option<u64>,
 }
 
 #[cw_serde]
 pub enum BalanceChangeListenerInterface {
     OnBalanceChange { event: BalanceChangeEvent },
+    OnBalanceChangeV2 { event: BalanceChangeEventV2 },
+}
+
+/// Running totals carried between pages of an Audit
+#[cw_serde]
+#[derive(Default)]
+pub struct AuditTotals {
+    pub n_balances: u64,
+    pub n_non_zero_balances: u64,
+    pub balance_sum: Uint128,
+    pub n_index_entries: u64,
+}
+
+#[cw_serde]
+pub enum AuditCursor {
+    /// Scanning BALANCES after the given account
+    Balances {
+        start_after: Addr,
+        totals: AuditTotals,
+    },
+    /// Scanning ORDERED_BALANCES after the given key, or from the start
+    OrderedBalances {
+        start_after: Option<(Uint128, Addr)>,
+        totals: AuditTotals,
+    },
+}
+
+#[cw_serde]
+pub enum AuditMismatch {
+    /// A non-zero balance has no ORDERED_BALANCES entry
+    MissingIndexEntry { address: Addr, balance: Uint128 },
+    /// An ORDERED_BALANCES entry doesn't match the account's balance
+    StaleIndexEntry {
+        address: Addr,
+        amount: Uint128,
+        balance: Uint128,
+    },
+    /// N_BALANCES differs from the number of non-zero balances
+    HolderCount { expected: Uint64, actual: Uint64 },
+    /// The sum of all balances differs from total supply
+    TotalSupply { expected: Uint128, actual: Uint128 },
+}
+
+#[cw_serde]
+pub struct AuditResponse {
+    /// Mismatches found on this page, up to a fixed maximum
+    pub mismatches: Vec<AuditMismatch>,
+    pub totals: AuditTotals,
+    pub cursor: Option<AuditCursor>,
+    pub done: bool,
+}
+
+/// State sections written by Export, in order
+#[cw_serde]
+pub enum ExportSection {
+    Config,
+    Balances,
+    Allowances,
+    FrozenAccounts,
+    ExcludedAccounts,
+    Listeners,
+    Subscriptions,
+    AccountStats,
+    History,
+    TransferHistory,
+}
+
+/// Token, marketing and operator settings
+#[cw_serde]
+pub struct ExportConfig {
+    pub token_info: TokenInfo,
+    pub marketing_info: Option<MarketingInfoResponse>,
+    pub logo: Option<Logo>,
+    pub operator: Option<Addr>,
+    pub global_freeze: bool,
+    pub tiers: Vec<HolderTier>,
+    pub transfer_history_size: u16,
+    pub subscription_deposit: Option<Coin>,
+    #[serde(default)]
+    pub legacy_tokens: Vec<LegacyToken>,
+}
+
+/// One entry of exported state. Indexes and aggregates derived from these,
+/// like ORDERED_BALANCES, holder counts and stats, are rebuilt on import.
+#[cw_serde]
+pub enum ExportRecord {
+    Config(Box<ExportConfig>),
+    Balance {
+        address: Addr,
+        amount: Uint128,
+    },
+    Allowance {
+        owner: Addr,
+        spender: Addr,
+        allowance: AllowanceResponse,
+    },
+    Frozen {
+        address: Addr,
+        frozen: bool,
+    },
+    Excluded {
+        address: Addr,
+    },
+    Listener {
+        address: Addr,
+        registration: ListenerRegistration,
+        config: Option<BalanceChangeListenerConfig>,
+        health: Option<ListenerHealth>,
+    },
+    /// The importer must attach the deposits of each page's subscriptions to
+    /// Import, so that unsubscribing refunds them from the new contract
+    Subscription {
+        subscriber: Addr,
+        subscription: Subscription,
+    },
+    AccountStats {
+        address: Addr,
+        stats: AccountStats,
+    },
+    History {
+        day: u64,
+        snapshot: HistorySnapshot,
+    },
+    TransferRecord {
+        address: Addr,
+        seq: u32,
+        record: TransferRecord,
+    },
+}
+
+/// Running checksum of exported records: a SHA-256 hash chained over the JSON
+/// of each record, along with the record count and sum of balances
+#[cw_serde]
+#[derive(Default)]
+pub struct ExportChecksum {
+    pub n_records: u64,
+    pub balance_sum: Uint128,
+    pub digest: HexBinary,
+}
+
+#[cw_serde]
+pub struct ExportCursor {
+    pub section: ExportSection,
+    /// Raw storage key of the last record exported from the section
+    pub start_after: Option<Binary>,
+    pub checksum: ExportChecksum,
+}
+
+#[cw_serde]
+pub struct ExportResponse {
+    pub records: Vec<ExportRecord>,
+    /// Checksum of all records exported so far, including this page
+    pub checksum: ExportChecksum,
+    pub cursor: Option<ExportCursor>,
+    pub done: bool,
+}
+
+/// Progress of an Import
+#[cw_serde]
+pub struct ImportState {
+    pub checksum: ExportChecksum,
+    /// Operator from the imported config, set once the import is done
+    pub operator: Option<Addr>,
+    pub done: bool,
+}
+
+/// Message embedded in a legacy token's Send to this contract
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Mint new tokens for the legacy tokens sent, to the recipient or else
+    /// the sender
+    Migrate { recipient: Option<String> },
+}
+
+/// What to do with legacy tokens once they've been swapped
+#[cw_serde]
+#[derive(Default)]
+pub enum LegacyTokenDisposal {
+    /// Keep them in this contract
+    #[default]
+    Hold,
+    /// Burn them through the legacy token
+    Burn,
+    /// Transfer them to another account, like a treasury
+    Forward { recipient: Addr },
+}
+
+/// Legacy CW20 accepted in exchange for this token
+#[cw_serde]
+pub struct LegacyToken {
+    pub address: Addr,
+    /// New tokens minted per legacy token received. Defaults to 1.
+    pub ratio: Option<Decimal>,
+    /// Defaults to Hold
+    pub disposal: Option<LegacyTokenDisposal>,
+}
+
+#[cw_serde]
+pub struct LegacyTokensResponse {
+    pub tokens: Vec<LegacyToken>,
 }
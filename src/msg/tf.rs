@@ -26,23 +26,21 @@ pub struct NewDenomMetadata {
 impl NewDenomMetadata {
     pub fn to_token_factory_metadata(
         &self,
-        factory: TokenFactoryType,
+        _factory: TokenFactoryType,
         full_denom: &String,
     ) -> Metadata {
-        let denom_units = match factory {
-            _ => vec![
-                DenomUnit {
-                    aliases: vec![],
-                    denom: full_denom.to_owned(),
-                    exponent: 0,
-                },
-                DenomUnit {
-                    aliases: vec![],
-                    denom: self.symbol.to_owned(),
-                    exponent: self.decimals,
-                },
-            ],
-        };
+        let denom_units = vec![
+            DenomUnit {
+                aliases: vec![],
+                denom: full_denom.to_owned(),
+                exponent: 0,
+            },
+            DenomUnit {
+                aliases: vec![],
+                denom: self.symbol.to_owned(),
+                exponent: self.decimals,
+            },
+        ];
         Metadata {
             symbol: self.symbol.to_owned(),
             display: self.symbol.to_owned(),
@@ -54,7 +52,7 @@ impl NewDenomMetadata {
         }
     }
 
-    pub fn build_micro_denom(full_denom: &String) -> String {
+    pub fn build_micro_denom(full_denom: &str) -> String {
         let mut parts = full_denom.split('/').map(|s| s.to_owned()).collect::<Vec<String>>();
 
         if !parts.is_empty() {
@@ -0,0 +1,55 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, Deps, Order, Uint64};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    msg::{AccountActivity, AccountStatsResponse, TopSendersResponse},
+    state::{ACCOUNT_STATS, ORDERED_SENDERS},
+};
+
+const DEFAULT_LIMIT: u16 = 50;
+const MAX_LIMIT: u16 = 200;
+
+pub fn query_account_stats(
+    deps: Deps,
+    address: Addr,
+) -> Result<AccountStatsResponse, ContractError> {
+    Ok(AccountStatsResponse {
+        stats: ACCOUNT_STATS.may_load(deps.storage, &address)?.unwrap_or_default(),
+        address,
+    })
+}
+
+/// Paginate accounts in descending order of the number of transfers sent
+pub fn query_top_senders(
+    deps: Deps,
+    limit: Option<u16>,
+    cursor: Option<(Uint64, Addr)>,
+) -> Result<TopSendersResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let max_bound = cursor
+        .as_ref()
+        .map(|(n, addr)| Bound::Exclusive(((n.u64(), addr), PhantomData)));
+
+    let mut senders: Vec<AccountActivity> = Vec::with_capacity(limit);
+    for result in ORDERED_SENDERS
+        .keys(deps.storage, None, max_bound, Order::Descending)
+        .take(limit)
+    {
+        let (_, address) = result?;
+        senders.push(AccountActivity {
+            stats: ACCOUNT_STATS.load(deps.storage, &address)?,
+            address,
+        });
+    }
+
+    let cursor = if senders.len() == limit {
+        senders.last().map(|s| (s.stats.n_sent, s.address.to_owned()))
+    } else {
+        None
+    };
+
+    Ok(TopSendersResponse { senders, cursor })
+}
@@ -0,0 +1,148 @@
+use cosmwasm_std::{Addr, Deps, Order, Uint128, Uint64};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+use cw_storage_plus::Bound;
+
+use crate::{
+    checks::ensure_index_ready,
+    error::ContractError,
+    math::add_u128,
+    msg::{AuditCursor, AuditMismatch, AuditResponse, AuditTotals},
+    state::{N_BALANCES, ORDERED_BALANCES},
+};
+
+const DEFAULT_LIMIT: u16 = 100;
+const MAX_LIMIT: u16 = 500;
+
+/// Max number of mismatches reported per page
+const MAX_MISMATCHES: usize = 20;
+
+/// Audit one page of state. BALANCES is scanned first, checking that each
+/// non-zero balance has its ORDERED_BALANCES entry, then ORDERED_BALANCES is
+/// scanned, checking that each entry matches the account's current balance.
+/// Together these ensure each holder has exactly one index entry. Holder count
+/// and total supply are checked against the running totals on the last page.
+pub fn query_audit(
+    deps: Deps,
+    limit: Option<u16>,
+    cursor: Option<AuditCursor>,
+) -> Result<AuditResponse, ContractError> {
+    ensure_index_ready(deps.storage)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let mut mismatches: Vec<AuditMismatch> = vec![];
+
+    let cursor = cursor.unwrap_or(AuditCursor::Balances {
+        start_after: Addr::unchecked(""),
+        totals: AuditTotals::default(),
+    });
+
+    let (cursor, totals, done) = match cursor {
+        AuditCursor::Balances {
+            start_after,
+            mut totals,
+        } => {
+            let entries = BALANCES
+                .range(
+                    deps.storage,
+                    Some(Bound::exclusive(&start_after)),
+                    None,
+                    Order::Ascending,
+                )
+                .take(limit)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for (address, balance) in entries.iter() {
+                totals.n_balances += 1;
+                totals.balance_sum = add_u128(totals.balance_sum, *balance)?;
+                if balance.is_zero() {
+                    continue;
+                }
+                totals.n_non_zero_balances += 1;
+                if !ORDERED_BALANCES.has(deps.storage, (balance.u128(), address)) && mismatches.len() < MAX_MISMATCHES {
+                    mismatches.push(AuditMismatch::MissingIndexEntry {
+                        address: address.to_owned(),
+                        balance: *balance,
+                    });
+                }
+            }
+
+            // Move on to the index once all balances have been scanned
+            let cursor = if entries.len() == limit {
+                AuditCursor::Balances {
+                    start_after: entries.last().map(|(a, _)| a.to_owned()).unwrap(),
+                    totals: totals.to_owned(),
+                }
+            } else {
+                AuditCursor::OrderedBalances {
+                    start_after: None,
+                    totals: totals.to_owned(),
+                }
+            };
+            (Some(cursor), totals, false)
+        },
+        AuditCursor::OrderedBalances {
+            start_after,
+            mut totals,
+        } => {
+            let keys = ORDERED_BALANCES
+                .keys(
+                    deps.storage,
+                    start_after
+                        .as_ref()
+                        .map(|(amount, address)| Bound::exclusive((amount.u128(), address))),
+                    None,
+                    Order::Ascending,
+                )
+                .take(limit)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for (amount, address) in keys.iter() {
+                totals.n_index_entries += 1;
+                let amount = Uint128::from(*amount);
+                let balance = BALANCES.may_load(deps.storage, address)?.unwrap_or_default();
+                if (amount != balance || amount.is_zero()) && mismatches.len() < MAX_MISMATCHES {
+                    mismatches.push(AuditMismatch::StaleIndexEntry {
+                        address: address.to_owned(),
+                        amount,
+                        balance,
+                    });
+                }
+            }
+
+            if keys.len() == limit {
+                let cursor = AuditCursor::OrderedBalances {
+                    start_after: keys
+                        .last()
+                        .map(|(amount, address)| (Uint128::from(*amount), address.to_owned())),
+                    totals: totals.to_owned(),
+                };
+                (Some(cursor), totals, false)
+            } else {
+                // Last page: check the aggregates against the running totals
+                let n_balances = N_BALANCES.may_load(deps.storage)?.unwrap_or_default();
+                let expected_n_balances = Uint64::from(totals.n_non_zero_balances);
+                if n_balances != expected_n_balances {
+                    mismatches.push(AuditMismatch::HolderCount {
+                        expected: expected_n_balances,
+                        actual: n_balances,
+                    });
+                }
+                let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+                if total_supply != totals.balance_sum {
+                    mismatches.push(AuditMismatch::TotalSupply {
+                        expected: totals.balance_sum,
+                        actual: total_supply,
+                    });
+                }
+                (None, totals, true)
+            }
+        },
+    };
+
+    Ok(AuditResponse {
+        mismatches,
+        totals,
+        cursor,
+        done,
+    })
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::marker::PhantomData;
 
 use cosmwasm_std::{Addr, Deps, Order, Uint128};
@@ -5,20 +6,28 @@ use cw20_base::state::BALANCES;
 use cw_storage_plus::Bound;
 
 use crate::{
+    checks::ensure_index_ready,
     error::ContractError,
     msg::{AccountBalance, BalancesResponse},
-    state::ORDERED_BALANCES,
+    state::{EXCLUDED_ACCOUNTS, ORDERED_BALANCES},
 };
 
 const DEFAULT_LIMIT: u16 = 50;
 const MAX_LIMIT: u16 = 500;
 
+#[allow(clippy::too_many_arguments)]
 pub fn query_paginate_balances(
     deps: Deps,
     limit: Option<u16>,
     desc: Option<bool>,
     cursor: Option<(Uint128, Addr)>,
+    min_amount: Option<Uint128>,
+    max_amount: Option<Uint128>,
+    exclude: Option<Vec<Addr>>,
+    include_excluded: Option<bool>,
 ) -> Result<BalancesResponse, ContractError> {
+    ensure_index_ready(deps.storage)?;
+
     // Default the limit param to something reasonable
     let cursor = cursor.unwrap_or_else(|| (Uint128::zero(), Addr::unchecked("")));
     let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
@@ -28,47 +37,86 @@ pub fn query_paginate_balances(
         Order::Ascending
     };
 
-    // Build starting point to begin or resume iteratation over balances map
+    if let (Some(min_amount), Some(max_amount)) = (min_amount, max_amount) {
+        if min_amount > max_amount {
+            return Err(ContractError::ValidationError {
+                reason: "min_amount cannot exceed max_amount".to_owned(),
+            });
+        }
+    }
+
+    let excluded: HashSet<Addr> = exclude.unwrap_or_default().into_iter().collect();
+    let include_excluded = include_excluded.unwrap_or(false);
+
+    // The empty address sorts before every other address, so pairing it with
+    // an amount gives us the first possible key for that amount.
+    let empty_addr = Addr::unchecked("");
+    let amount_min_bound = min_amount.map(|n| Bound::Inclusive(((n.u128(), &empty_addr), PhantomData)));
+    let amount_max_bound = max_amount.and_then(|n| {
+        n.u128()
+            .checked_add(1)
+            .map(|n| Bound::Exclusive(((n, &empty_addr), PhantomData)))
+    });
+
+    // Build starting point to begin or resume iteratation over balances map,
+    // narrowed by the requested amount range.
     let (min_bound, max_bound) = if !cursor.0.is_zero() {
+        let cursor_bound = Bound::Exclusive(((cursor.0.u128(), &cursor.1), PhantomData));
         match order {
             Order::Ascending => (
-                Some(Bound::Exclusive(((cursor.0.u128(), &cursor.1), PhantomData))),
-                None,
+                if min_amount.map(|n| cursor.0 < n).unwrap_or(false) {
+                    amount_min_bound
+                } else {
+                    Some(cursor_bound)
+                },
+                amount_max_bound,
             ),
             Order::Descending => (
-                None,
-                Some(Bound::Exclusive(((cursor.0.u128(), &cursor.1), PhantomData))),
+                amount_min_bound,
+                if max_amount.map(|n| cursor.0 > n).unwrap_or(false) {
+                    amount_max_bound
+                } else {
+                    Some(cursor_bound)
+                },
             ),
         }
     } else {
-        (None, None)
+        (amount_min_bound, amount_max_bound)
     };
 
-    // Iterate through balances in order, create AccountBalances to return
-    let balances: Vec<AccountBalance> = ORDERED_BALANCES
+    // Iterate through balances in order, skipping excluded addresses, and
+    // create AccountBalances to return
+    let mut keys = ORDERED_BALANCES
         .keys(deps.storage, min_bound, max_bound, order)
-        .take(limit)
-        .map(|result| {
-            let (amount, address) = result.unwrap();
-            AccountBalance {
-                amount: amount.into(),
-                address,
-            }
+        .filter(|result| match result {
+            Ok((_, address)) => {
+                !excluded.contains(address) && (include_excluded || !EXCLUDED_ACCOUNTS.has(deps.storage, address))
+            },
+            Err(_) => true,
         })
-        .collect();
+        .peekable();
+
+    let mut balances: Vec<AccountBalance> = Vec::with_capacity(limit);
+    while balances.len() < limit {
+        match keys.next() {
+            Some(result) => {
+                let (amount, address) = result?;
+                balances.push(AccountBalance {
+                    amount: amount.into(),
+                    address,
+                });
+            },
+            None => break,
+        }
+    }
 
     // Return a cursor to to resume iteration in a follow-up query. Only return
     // a non-null cursor if the number of elements being returned equals the
-    // input limit param AND the last element in the batch isn't equal to the
-    // final ranked balance in the storage map.
+    // input limit param AND there's at least one more matching balance left.
     let mut next_cursor: Option<(Uint128, Addr)> = None;
-    if balances.len() == limit {
-        if let Some(((_, final_address), _)) = ORDERED_BALANCES.last(deps.storage)? {
-            let tail = balances.last().unwrap();
-            if tail.address != final_address {
-                next_cursor = Some((tail.amount, tail.address.clone()));
-            }
-        }
+    if balances.len() == limit && keys.peek().is_some() {
+        let tail = balances.last().unwrap();
+        next_cursor = Some((tail.amount, tail.address.clone()));
     }
 
     Ok(BalancesResponse {
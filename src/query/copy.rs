@@ -0,0 +1,48 @@
+use cosmwasm_std::{Deps, Env};
+
+use crate::{
+    copy::{load_copy_job, load_copy_source_kind, local_bech32_prefix, plan_copy_batch},
+    error::ContractError,
+    msg::{BalanceCopyMode, CopyCapBehavior, CopyJobResponse, CopyPreviewResponse, CopySource},
+    state::{BALANCE_COPY_CURSORS, BALANCE_COPY_JOB},
+};
+
+pub fn query_copy_job(deps: Deps) -> Result<CopyJobResponse, ContractError> {
+    let (source, cursor) = match BALANCE_COPY_CURSORS.first(deps.storage)? {
+        Some((source, cursor)) => (Some(source), Some(cursor)),
+        None => (None, None),
+    };
+    Ok(CopyJobResponse {
+        source,
+        cursor,
+        job: BALANCE_COPY_JOB.may_load(deps.storage)?,
+    })
+}
+
+/// Preview the changes the next CopyBalances call would make with the given
+/// arguments, validated against the copy in progress the same way. Balances in
+/// the source may change before it's executed.
+pub fn query_copy_preview(
+    deps: Deps,
+    env: Env,
+    source: CopySource,
+    mode: BalanceCopyMode,
+    cap_behavior: Option<CopyCapBehavior>,
+    remap_prefix: Option<bool>,
+) -> Result<CopyPreviewResponse, ContractError> {
+    let source_kind = load_copy_source_kind(deps, &source)?;
+    let job = load_copy_job(deps.storage, &source, &source_kind, &mode, cap_behavior, remap_prefix)?;
+    let prefix = match job.remap_prefix {
+        true => Some(local_bech32_prefix(&env)?),
+        false => None,
+    };
+    let batch = plan_copy_batch(deps, &source, &source_kind, &mode, &job.cap_behavior, prefix.as_deref())?;
+    Ok(CopyPreviewResponse {
+        source_kind,
+        done: batch.cursor.is_none(),
+        changes: batch.changes,
+        total_supply: batch.total_supply,
+        skipped: batch.skipped,
+        cursor: batch.cursor,
+    })
+}
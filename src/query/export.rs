@@ -0,0 +1,198 @@
+use cosmwasm_std::{Binary, Deps, Order, StdResult, Storage};
+use cw20_base::state::{ALLOWANCES, BALANCES, LOGO, MARKETING_INFO, TOKEN_INFO};
+use cw_storage_plus::{Bound, KeyDeserialize, Map, PrimaryKey};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::ContractError,
+    export::{next_export_section, update_export_checksum},
+    msg::{ExportChecksum, ExportConfig, ExportCursor, ExportRecord, ExportResponse, ExportSection},
+    state::{
+        ACCOUNT_STATS, BALANCE_CHANGE_LISTENERS, BALANCE_CHANGE_LISTENER_CONFIGS, EXCLUDED_ACCOUNTS, FROZEN_ACCOUNTS,
+        GLOBAL_BALANCE_FREEZE, HISTORY, LEGACY_TOKENS, LISTENER_HEALTH, OPERATOR_ADDR, SUBSCRIPTIONS,
+        SUBSCRIPTION_DEPOSIT, TIERS, TRANSFER_HISTORY, TRANSFER_HISTORY_SIZE,
+    },
+};
+
+const DEFAULT_LIMIT: u16 = 100;
+const MAX_LIMIT: u16 = 500;
+
+/// Export one page of records, moving through the sections in order and
+/// continuing into the next section when one runs out before the page is full.
+/// Transient state, like copy and reindex progress and listener retry queues,
+/// isn't exported, nor is TokenFactory state tied to this contract's address.
+pub fn query_export(
+    deps: Deps,
+    limit: Option<u16>,
+    cursor: Option<ExportCursor>,
+) -> Result<ExportResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let ExportCursor {
+        mut section,
+        mut start_after,
+        mut checksum,
+    } = cursor.unwrap_or(ExportCursor {
+        section: ExportSection::Config,
+        start_after: None,
+        checksum: ExportChecksum::default(),
+    });
+
+    let mut records: Vec<ExportRecord> = Vec::with_capacity(limit);
+    let mut done = false;
+
+    loop {
+        let remaining = limit - records.len();
+        let (page, last_key) = export_section(deps.storage, &section, start_after.take(), remaining)?;
+        let n_exported = page.len();
+        records.extend(page);
+        if n_exported == remaining {
+            start_after = last_key;
+            break;
+        }
+        match next_export_section(&section) {
+            Some(next) => section = next,
+            None => {
+                done = true;
+                break;
+            },
+        }
+    }
+
+    for record in records.iter() {
+        update_export_checksum(&mut checksum, record)?;
+    }
+
+    let cursor = if done {
+        None
+    } else {
+        Some(ExportCursor {
+            section,
+            start_after,
+            checksum: checksum.to_owned(),
+        })
+    };
+
+    Ok(ExportResponse {
+        records,
+        checksum,
+        cursor,
+        done,
+    })
+}
+
+/// Export up to `limit` records from the section, returning them with the raw
+/// key of the last one
+fn export_section(
+    store: &dyn Storage,
+    section: &ExportSection,
+    start_after: Option<Binary>,
+    limit: usize,
+) -> Result<(Vec<ExportRecord>, Option<Binary>), ContractError> {
+    match section {
+        ExportSection::Config => {
+            if start_after.is_some() {
+                return Ok((vec![], None));
+            }
+            let config = ExportConfig {
+                token_info: TOKEN_INFO.load(store)?,
+                marketing_info: MARKETING_INFO.may_load(store)?,
+                logo: LOGO.may_load(store)?,
+                operator: OPERATOR_ADDR.may_load(store)?,
+                global_freeze: GLOBAL_BALANCE_FREEZE.may_load(store)?.unwrap_or(false),
+                tiers: TIERS.may_load(store)?.unwrap_or_default(),
+                transfer_history_size: TRANSFER_HISTORY_SIZE.may_load(store)?.unwrap_or_default(),
+                subscription_deposit: SUBSCRIPTION_DEPOSIT.may_load(store)?,
+                legacy_tokens: LEGACY_TOKENS
+                    .range(store, None, None, Order::Ascending)
+                    .map(|r| r.map(|(_, token)| token))
+                    .collect::<StdResult<Vec<_>>>()?,
+            };
+            Ok((vec![ExportRecord::Config(Box::new(config))], Some(Binary::default())))
+        },
+        ExportSection::Balances => export_map(store, &BALANCES, start_after, limit, |address, amount| {
+            Ok(ExportRecord::Balance { address, amount })
+        }),
+        ExportSection::Allowances => {
+            export_map(store, &ALLOWANCES, start_after, limit, |(owner, spender), allowance| {
+                Ok(ExportRecord::Allowance {
+                    owner,
+                    spender,
+                    allowance,
+                })
+            })
+        },
+        ExportSection::FrozenAccounts => export_map(store, &FROZEN_ACCOUNTS, start_after, limit, |address, frozen| {
+            Ok(ExportRecord::Frozen { address, frozen })
+        }),
+        ExportSection::ExcludedAccounts => export_map(store, &EXCLUDED_ACCOUNTS, start_after, limit, |address, _| {
+            Ok(ExportRecord::Excluded { address })
+        }),
+        ExportSection::Listeners => export_map(
+            store,
+            &BALANCE_CHANGE_LISTENERS,
+            start_after,
+            limit,
+            |address, registration| {
+                Ok(ExportRecord::Listener {
+                    config: BALANCE_CHANGE_LISTENER_CONFIGS.may_load(store, &address)?,
+                    health: LISTENER_HEALTH.may_load(store, &address)?,
+                    address,
+                    registration,
+                })
+            },
+        ),
+        ExportSection::Subscriptions => {
+            export_map(store, &SUBSCRIPTIONS, start_after, limit, |subscriber, subscription| {
+                Ok(ExportRecord::Subscription {
+                    subscriber,
+                    subscription,
+                })
+            })
+        },
+        ExportSection::AccountStats => export_map(store, &ACCOUNT_STATS, start_after, limit, |address, stats| {
+            Ok(ExportRecord::AccountStats { address, stats })
+        }),
+        ExportSection::History => export_map(store, &HISTORY, start_after, limit, |day, snapshot| {
+            Ok(ExportRecord::History { day, snapshot })
+        }),
+        ExportSection::TransferHistory => export_map(
+            store,
+            &TRANSFER_HISTORY,
+            start_after,
+            limit,
+            |(address, seq), record| Ok(ExportRecord::TransferRecord { address, seq, record }),
+        ),
+    }
+}
+
+/// Export a page of map entries in raw key order, resuming after the given raw
+/// key so that every section can share one cursor format
+fn export_map<'a, K, T>(
+    store: &dyn Storage,
+    map: &Map<'a, K, T>,
+    start_after: Option<Binary>,
+    limit: usize,
+    to_record: impl Fn(K::Output, T) -> Result<ExportRecord, ContractError>,
+) -> Result<(Vec<ExportRecord>, Option<Binary>), ContractError>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    T: Serialize + DeserializeOwned,
+{
+    let entries = map
+        .range_raw(
+            store,
+            start_after.map(|key| Bound::ExclusiveRaw(key.to_vec())),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let last_key = entries.last().map(|(key, _)| Binary::from(key.as_slice()));
+    let mut records: Vec<ExportRecord> = Vec::with_capacity(entries.len());
+    for (key, value) in entries {
+        records.push(to_record(K::from_vec(key)?, value)?);
+    }
+
+    Ok((records, last_key))
+}
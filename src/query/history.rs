@@ -0,0 +1,46 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Deps, Order, Timestamp};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    msg::{HistoryResponse, HistorySnapshot},
+    state::HISTORY,
+};
+
+const DEFAULT_LIMIT: u16 = 30;
+const MAX_LIMIT: u16 = 366;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Paginate daily snapshots in chronological order between the given times,
+/// inclusive. Resume by passing the returned cursor as `from`.
+pub fn query_history(
+    deps: Deps,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+    limit: Option<u16>,
+) -> Result<HistoryResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let min_bound = from.map(|t| Bound::Inclusive((t.seconds() / SECONDS_PER_DAY, PhantomData)));
+    let max_bound = to.map(|t| Bound::Inclusive((t.seconds() / SECONDS_PER_DAY, PhantomData)));
+
+    let mut entries = HISTORY
+        .range(deps.storage, min_bound, max_bound, Order::Ascending)
+        .peekable();
+
+    let mut snapshots: Vec<HistorySnapshot> = Vec::with_capacity(limit);
+    while snapshots.len() < limit {
+        match entries.next() {
+            Some(result) => snapshots.push(result?.1),
+            None => break,
+        }
+    }
+
+    let cursor = match entries.peek() {
+        Some(Ok((day, _))) => Some(Timestamp::from_seconds(day * SECONDS_PER_DAY)),
+        _ => None,
+    };
+
+    Ok(HistoryResponse { snapshots, cursor })
+}
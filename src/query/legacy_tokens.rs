@@ -0,0 +1,11 @@
+use cosmwasm_std::{Deps, Order};
+
+use crate::{error::ContractError, msg::LegacyTokensResponse, state::LEGACY_TOKENS};
+
+pub fn query_legacy_tokens(deps: Deps) -> Result<LegacyTokensResponse, ContractError> {
+    let tokens = LEGACY_TOKENS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, token)| token))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(LegacyTokensResponse { tokens })
+}
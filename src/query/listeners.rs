@@ -0,0 +1,130 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, Deps, Order};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    listeners::load_listener_health,
+    msg::{
+        BalanceChangeListenerConfig, ListenerHealthResponse, ListenerInfo, ListenerQueueEntry, ListenerQueueResponse,
+        ListenerStatus, ListenersResponse,
+    },
+    state::{
+        BALANCE_CHANGE_LISTENERS, BALANCE_CHANGE_LISTENER_CONFIGS, LISTENER_RETRY_QUEUE, LISTENER_RETRY_SEQS,
+        MAX_LISTENER_RETRY_QUEUE_SIZE,
+    },
+};
+
+const DEFAULT_LIMIT: u16 = 20;
+const MAX_LIMIT: u16 = 100;
+
+pub fn query_listener_health(deps: Deps) -> Result<ListenerHealthResponse, ContractError> {
+    let mut listeners: Vec<ListenerStatus> = vec![];
+    for result in BALANCE_CHANGE_LISTENERS.keys(deps.storage, None, None, Order::Ascending) {
+        let address = result?;
+        listeners.push(ListenerStatus {
+            health: load_listener_health(deps.storage, &address)?,
+            address,
+        });
+    }
+    Ok(ListenerHealthResponse { listeners })
+}
+
+/// Paginate registered listeners in address order
+pub fn query_listeners(
+    deps: Deps,
+    limit: Option<u16>,
+    cursor: Option<Addr>,
+) -> Result<ListenersResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let entries = BALANCE_CHANGE_LISTENERS
+        .range(
+            deps.storage,
+            cursor.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut listeners: Vec<ListenerInfo> = Vec::with_capacity(entries.len());
+    for (address, registration) in entries {
+        // Listeners registered before configs existed have default settings
+        let config = BALANCE_CHANGE_LISTENER_CONFIGS
+            .may_load(deps.storage, &address)?
+            .unwrap_or(BalanceChangeListenerConfig {
+                address: address.to_owned(),
+                filter: None,
+                gas_limit: None,
+                max_failures: None,
+                event_version: None,
+            });
+        listeners.push(ListenerInfo {
+            config,
+            registration,
+            health: load_listener_health(deps.storage, &address)?,
+            n_queued: count_queued_events(deps, &address)?,
+        });
+    }
+
+    let cursor = if listeners.len() == limit {
+        listeners.last().map(|l| l.config.address.to_owned())
+    } else {
+        None
+    };
+
+    Ok(ListenersResponse { listeners, cursor })
+}
+
+fn count_queued_events(
+    deps: Deps,
+    listener: &Addr,
+) -> Result<u64, ContractError> {
+    let next_seq = LISTENER_RETRY_SEQS
+        .may_load(deps.storage, listener)?
+        .unwrap_or_default();
+    let min_seq = next_seq.saturating_sub(MAX_LISTENER_RETRY_QUEUE_SIZE);
+    Ok(LISTENER_RETRY_QUEUE
+        .prefix(listener)
+        .keys(
+            deps.storage,
+            Some(Bound::Inclusive((min_seq, PhantomData))),
+            None,
+            Order::Ascending,
+        )
+        .count() as u64)
+}
+
+/// Paginate the events queued for redelivery to a listener, oldest first
+pub fn query_listener_queue(
+    deps: Deps,
+    listener: Addr,
+    limit: Option<u16>,
+    cursor: Option<u64>,
+) -> Result<ListenerQueueResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let next_seq = LISTENER_RETRY_SEQS
+        .may_load(deps.storage, &listener)?
+        .unwrap_or_default();
+    let min_seq = next_seq.saturating_sub(MAX_LISTENER_RETRY_QUEUE_SIZE);
+    let min_bound = match cursor {
+        Some(seq) if seq >= min_seq => Bound::Exclusive((seq, PhantomData)),
+        _ => Bound::Inclusive((min_seq, PhantomData)),
+    };
+
+    let events = LISTENER_RETRY_QUEUE
+        .prefix(&listener)
+        .range(deps.storage, Some(min_bound), None, Order::Ascending)
+        .take(limit)
+        .map(|result| result.map(|(seq, queued)| ListenerQueueEntry { seq, queued }))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cursor = if events.len() == limit {
+        events.last().map(|e| e.seq)
+    } else {
+        None
+    };
+
+    Ok(ListenerQueueResponse { events, cursor })
+}
@@ -1 +1,13 @@
+pub mod activity;
+pub mod audit;
 pub mod balances;
+pub mod copy;
+pub mod export;
+pub mod history;
+pub mod legacy_tokens;
+pub mod listeners;
+pub mod stats;
+pub mod subscriptions;
+pub mod supply;
+pub mod tiers;
+pub mod transfer_history;
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Decimal, Decimal256, Deps, Order, StdError, Uint128, Uint256, Uint64};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+use crate::{
+    checks::ensure_index_ready,
+    error::ContractError,
+    math::{add_u128, add_u256, mul_u256},
+    msg::{HistogramBucket, StatsResponse},
+    state::{BALANCE_HISTOGRAM, EXCLUDED_ACCOUNTS, N_BALANCES, ORDERED_BALANCES},
+    stats::{histogram_bucket, load_sum_squared_balances},
+};
+
+const TOP_N_SMALL: usize = 10;
+const TOP_N_LARGE: usize = 100;
+
+/// Distribution stats over all holders or, unless include_excluded is set,
+/// over holders outside the excluded-account registry. Leaving excluded
+/// accounts out matches the HolderCount query, and measures shares and HHI
+/// against circulating supply so that treasury and LP balances don't inflate
+/// concentration. The registry is small, so its balances are subtracted from
+/// the maintained totals here.
+pub fn query_stats(
+    deps: Deps,
+    include_excluded: Option<bool>,
+) -> Result<StatsResponse, ContractError> {
+    ensure_index_ready(deps.storage)?;
+
+    let mut total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let mut n_holders = N_BALANCES.load(deps.storage)?;
+    let mut sum_squares = load_sum_squared_balances(deps.storage)?;
+    let mut excluded_buckets: BTreeMap<u8, (Uint64, Uint128)> = BTreeMap::new();
+
+    if !include_excluded.unwrap_or(false) {
+        let mut excluded_squares = Uint256::zero();
+        for result in EXCLUDED_ACCOUNTS.keys(deps.storage, None, None, Order::Ascending) {
+            let balance = BALANCES.may_load(deps.storage, &result?)?.unwrap_or_default();
+            if balance.is_zero() {
+                continue;
+            }
+            total_supply = total_supply.saturating_sub(balance);
+            n_holders = n_holders.saturating_sub(Uint64::one());
+            excluded_squares = add_u256(excluded_squares, mul_u256(balance, balance)?)?;
+            let (n, amount) = excluded_buckets.entry(histogram_bucket(balance)).or_default();
+            *n += Uint64::one();
+            *amount = add_u128(*amount, balance)?;
+        }
+        // Saturate in case the stats lag behind the balances, as they can on
+        // state that hasn't been reindexed since being migrated
+        sum_squares = sum_squares.saturating_sub(excluded_squares);
+    }
+
+    // Sum the largest balances for the top-N shares
+    let mut top_small_amount = Uint128::zero();
+    let mut top_large_amount = Uint128::zero();
+    for (i, result) in ORDERED_BALANCES
+        .keys(deps.storage, None, None, Order::Descending)
+        .filter(|result| match result {
+            Ok((_, address)) => excluded_buckets.is_empty() || !EXCLUDED_ACCOUNTS.has(deps.storage, address),
+            Err(_) => true,
+        })
+        .take(TOP_N_LARGE)
+        .enumerate()
+    {
+        let (amount, _) = result?;
+        if i < TOP_N_SMALL {
+            top_small_amount = add_u128(top_small_amount, amount)?;
+        }
+        top_large_amount = add_u128(top_large_amount, amount)?;
+    }
+
+    let share = |amount: Uint128| -> Decimal {
+        if total_supply.is_zero() {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(amount, total_supply)
+        }
+    };
+
+    // HHI is the sum of squared supply shares, i.e. sum(b^2) / supply^2
+    let hhi = if total_supply.is_zero() {
+        Decimal::zero()
+    } else {
+        let supply_squared = mul_u256(total_supply, total_supply)?;
+        let hhi = Decimal256::checked_from_ratio(sum_squares, supply_squared)
+            .map_err(|e| ContractError::Std(StdError::generic_err(e.to_string())))?;
+        Decimal::try_from(hhi).map_err(|e| ContractError::Std(StdError::generic_err(e.to_string())))?
+    };
+
+    let mut histogram: Vec<HistogramBucket> = vec![];
+    for result in BALANCE_HISTOGRAM.range(deps.storage, None, None, Order::Ascending) {
+        let (exponent, (mut n_holders, mut amount)) = result?;
+        if let Some((n_excluded, excluded_amount)) = excluded_buckets.get(&exponent) {
+            n_holders = n_holders.saturating_sub(*n_excluded);
+            amount = amount.saturating_sub(*excluded_amount);
+        }
+        if !n_holders.is_zero() {
+            histogram.push(HistogramBucket {
+                min_balance: Uint128::new(10u128.pow(exponent as u32)),
+                n_holders,
+                amount,
+            });
+        }
+    }
+
+    Ok(StatsResponse {
+        top_10_share: share(top_small_amount),
+        top_100_share: share(top_large_amount),
+        n_holders,
+        total_supply,
+        hhi,
+        histogram,
+    })
+}
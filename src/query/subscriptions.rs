@@ -0,0 +1,14 @@
+use cosmwasm_std::{Addr, Deps};
+
+use crate::{error::ContractError, listeners::load_listener_health, msg::SubscriptionResponse, state::SUBSCRIPTIONS};
+
+pub fn query_subscription(
+    deps: Deps,
+    subscriber: Addr,
+) -> Result<SubscriptionResponse, ContractError> {
+    Ok(SubscriptionResponse {
+        subscription: SUBSCRIPTIONS.may_load(deps.storage, &subscriber)?,
+        health: load_listener_health(deps.storage, &subscriber)?,
+        subscriber,
+    })
+}
@@ -0,0 +1,87 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, Deps, Order, Storage, Uint128, Uint64};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+use cw_storage_plus::Bound;
+
+use crate::{
+    checks::ensure_index_ready,
+    error::ContractError,
+    math::add_u128,
+    msg::{AccountBalance, CirculatingSupplyResponse, ExcludedAccountsResponse, HolderCountResponse},
+    state::{EXCLUDED_ACCOUNTS, N_BALANCES},
+};
+
+const DEFAULT_LIMIT: u16 = 50;
+const MAX_LIMIT: u16 = 100;
+
+/// Return the number of excluded accounts with a non-zero balance along with
+/// the sum of their balances.
+pub fn load_excluded_balances(store: &dyn Storage) -> Result<(Uint64, Uint128), ContractError> {
+    let mut n_holders = Uint64::zero();
+    let mut total = Uint128::zero();
+    for result in EXCLUDED_ACCOUNTS.keys(store, None, None, Order::Ascending) {
+        let balance = BALANCES.may_load(store, &result?)?.unwrap_or_default();
+        if !balance.is_zero() {
+            n_holders += Uint64::one();
+            total = add_u128(total, balance)?;
+        }
+    }
+    Ok((n_holders, total))
+}
+
+/// Total supply minus excluded balances. Vesting isn't tracked by this
+/// contract, since schedules live in the vesting contracts themselves. Tokens
+/// stay in a vesting contract until they're claimed, so registering it as an
+/// excluded account leaves its locked balance out of circulating supply.
+pub fn query_circulating_supply(deps: Deps) -> Result<CirculatingSupplyResponse, ContractError> {
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let (_, excluded_supply) = load_excluded_balances(deps.storage)?;
+    Ok(CirculatingSupplyResponse {
+        circulating_supply: total_supply.saturating_sub(excluded_supply),
+        excluded_supply,
+        total_supply,
+    })
+}
+
+pub fn query_holder_count(deps: Deps) -> Result<HolderCountResponse, ContractError> {
+    ensure_index_ready(deps.storage)?;
+
+    let n_balances = N_BALANCES.load(deps.storage)?;
+    let (n_excluded, _) = load_excluded_balances(deps.storage)?;
+    // Saturate in case N_BALANCES lags behind the balances, as it can on state
+    // that hasn't been reindexed since being migrated
+    Ok(HolderCountResponse {
+        n_holders: n_balances.saturating_sub(n_excluded),
+        n_excluded,
+    })
+}
+
+pub fn query_excluded_accounts(
+    deps: Deps,
+    limit: Option<u16>,
+    cursor: Option<Addr>,
+) -> Result<ExcludedAccountsResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let min_bound = cursor.as_ref().map(|addr| Bound::Exclusive((addr, PhantomData)));
+
+    let mut accounts: Vec<AccountBalance> = Vec::with_capacity(limit);
+    for result in EXCLUDED_ACCOUNTS
+        .keys(deps.storage, min_bound, None, Order::Ascending)
+        .take(limit)
+    {
+        let address = result?;
+        accounts.push(AccountBalance {
+            amount: BALANCES.may_load(deps.storage, &address)?.unwrap_or_default(),
+            address,
+        });
+    }
+
+    let cursor = if accounts.len() == limit {
+        accounts.last().map(|a| a.address.to_owned())
+    } else {
+        None
+    };
+
+    Ok(ExcludedAccountsResponse { accounts, cursor })
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::{Addr, Deps, Order, Uint128, Uint64};
+use cw20_base::state::BALANCES;
+
+use crate::{
+    checks::ensure_index_ready,
+    error::ContractError,
+    msg::{AccountTierResponse, BalancesResponse, TierStats, TiersResponse},
+    query::balances::query_paginate_balances,
+    state::{EXCLUDED_ACCOUNTS, N_TIER_HOLDERS, TIERS},
+    tiers::{find_tier, tier_upper_bound},
+};
+
+/// Tiers along with their holder counts. Like the HolderCount query, accounts
+/// in the excluded-account registry aren't counted unless include_excluded is
+/// set. The registry is small, so they're subtracted from the maintained
+/// counts here.
+pub fn query_tiers(
+    deps: Deps,
+    include_excluded: Option<bool>,
+) -> Result<TiersResponse, ContractError> {
+    ensure_index_ready(deps.storage)?;
+
+    let tiers = TIERS.may_load(deps.storage)?.unwrap_or_default();
+
+    let mut n_excluded: HashMap<String, Uint64> = HashMap::new();
+    if !include_excluded.unwrap_or(false) {
+        for result in EXCLUDED_ACCOUNTS.keys(deps.storage, None, None, Order::Ascending) {
+            let balance = BALANCES.may_load(deps.storage, &result?)?.unwrap_or_default();
+            if let Some(tier) = find_tier(&tiers, balance) {
+                *n_excluded.entry(tier.name.to_owned()).or_default() += Uint64::one();
+            }
+        }
+    }
+
+    let mut tier_stats: Vec<TierStats> = vec![];
+    for tier in tiers {
+        let n_holders = N_TIER_HOLDERS.may_load(deps.storage, &tier.name)?.unwrap_or_default();
+        tier_stats.push(TierStats {
+            n_holders: n_holders.saturating_sub(n_excluded.get(&tier.name).copied().unwrap_or_default()),
+            tier,
+        });
+    }
+    Ok(TiersResponse { tiers: tier_stats })
+}
+
+pub fn query_account_tier(
+    deps: Deps,
+    address: Addr,
+) -> Result<AccountTierResponse, ContractError> {
+    let tiers = TIERS.may_load(deps.storage)?.unwrap_or_default();
+    let balance = BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(AccountTierResponse {
+        tier: find_tier(&tiers, balance).cloned(),
+        address,
+        balance,
+    })
+}
+
+/// Paginate the balances of holders whose balance falls within the named tier,
+/// skipping excluded accounts unless include_excluded is set
+pub fn query_tier_holders(
+    deps: Deps,
+    tier: String,
+    limit: Option<u16>,
+    desc: Option<bool>,
+    cursor: Option<(Uint128, Addr)>,
+    include_excluded: Option<bool>,
+) -> Result<BalancesResponse, ContractError> {
+    let tiers = TIERS.may_load(deps.storage)?.unwrap_or_default();
+    let index = tiers
+        .iter()
+        .position(|t| t.name == tier)
+        .ok_or_else(|| ContractError::ValidationError {
+            reason: format!("tier {} does not exist", tier),
+        })?;
+
+    let min_amount = tiers[index].min_balance;
+    let max_amount = tier_upper_bound(&tiers, index).map(|n| n - Uint128::one());
+
+    query_paginate_balances(
+        deps,
+        limit,
+        desc,
+        cursor,
+        Some(min_amount),
+        max_amount,
+        None,
+        include_excluded,
+    )
+}
@@ -0,0 +1,56 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, Deps, Order};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    msg::{TransferHistoryResponse, TransferRecord},
+    state::{TRANSFER_HISTORY, TRANSFER_HISTORY_SEQS, TRANSFER_HISTORY_SIZE},
+};
+
+const DEFAULT_LIMIT: u16 = 20;
+const MAX_LIMIT: u16 = 100;
+
+/// Paginate an account's recent transfers, most recent first
+pub fn query_transfer_history(
+    deps: Deps,
+    address: Addr,
+    limit: Option<u16>,
+    cursor: Option<u32>,
+) -> Result<TransferHistoryResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let size = TRANSFER_HISTORY_SIZE.may_load(deps.storage)?.unwrap_or_default() as u32;
+    let next_seq = TRANSFER_HISTORY_SEQS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+
+    // Skip entries that fall outside of the buffer, in case its size has been
+    // reduced since they were written
+    let min_seq = next_seq.saturating_sub(size);
+    let max_seq = cursor.unwrap_or(next_seq).min(next_seq);
+
+    let mut transfers: Vec<TransferRecord> = Vec::with_capacity(limit);
+    let mut seqs: Vec<u32> = Vec::with_capacity(limit);
+    for result in TRANSFER_HISTORY
+        .prefix(&address)
+        .range(
+            deps.storage,
+            Some(Bound::Inclusive((min_seq, PhantomData))),
+            Some(Bound::Exclusive((max_seq, PhantomData))),
+            Order::Descending,
+        )
+        .take(limit)
+    {
+        let (seq, record) = result?;
+        seqs.push(seq);
+        transfers.push(record);
+    }
+
+    let cursor = match seqs.last() {
+        Some(seq) if transfers.len() == limit && *seq > min_seq => Some(*seq),
+        _ => None,
+    };
+
+    Ok(TransferHistoryResponse { transfers, cursor })
+}
@@ -0,0 +1,32 @@
+use crate::{
+    error::ContractError,
+    listeners::{enqueue_listener_retry, record_listener_failure},
+    state::LISTENER_DELIVERIES,
+};
+use cosmwasm_std::{attr, DepsMut, Env, Reply, Response, SubMsgResult};
+
+/// Handle a failed balance change listener submsg by recording the failure and
+/// queueing the event for retry, instead of reverting the whole tx
+pub fn reply_listener(
+    deps: DepsMut,
+    env: Env,
+    reply: Reply,
+) -> Result<Response, ContractError> {
+    let error = match reply.result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+    };
+
+    let (listener, delivery, queued) = LISTENER_DELIVERIES.load(deps.storage, reply.id)?;
+    LISTENER_DELIVERIES.remove(deps.storage, reply.id);
+
+    let health = record_listener_failure(deps.storage, &listener, delivery, env.block.height, error)?;
+    enqueue_listener_retry(deps.storage, &listener, &queued)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "listener_failure"),
+        attr("listener", listener.to_string()),
+        attr("n_failures", health.n_failures.to_string()),
+        attr("disabled", health.disabled.to_string()),
+    ]))
+}
@@ -2,7 +2,7 @@ pub mod tf;
 
 use std::collections::HashMap;
 
-use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdError, Uint128, Uint64};
+use cosmwasm_std::{Addr, Coin, DepsMut, Env, MessageInfo, Response, StdError, Uint128, Uint256, Uint64};
 use cw20::{Cw20Coin, EmbeddedLogo, Logo, LogoInfo, MarketingInfoResponse};
 use cw20_base::{
     contract::create_accounts,
@@ -11,17 +11,125 @@ use cw20_base::{
 use cw_storage_plus::{Item, Map};
 use tf::TF_N_BALANCES_INITIALIZED;
 
-use crate::{error::ContractError, math::add_u128};
+use crate::{
+    error::ContractError,
+    math::add_u128,
+    msg::{
+        AccountStats, BalanceChangeListenerConfig, BalanceCopyJob, HistorySnapshot, HolderTier, ImportState,
+        LegacyToken, ListenerHealth, ListenerRegistration, QueuedBalanceChangeEvent, ReindexState, Subscription,
+        TransferRecord,
+    },
+    stats::update_balance_stats,
+};
 
 const LOGO_SIZE_CAP: usize = 5 * 1024;
 
 pub const OPERATOR_ADDR: Item<Addr> = Item::new("operator_addr");
 pub const ORDERED_BALANCES: Map<(u128, &Addr), u8> = Map::new("ordered_balances");
 pub const N_BALANCES: Item<Uint64> = Item::new("n_balances");
+/// Progress of an in-progress Reindex, if any
+pub const REINDEX_STATE: Item<ReindexState> = Item::new("reindex_state");
+/// Progress of an Import from another contract's Export
+pub const IMPORT_STATE: Item<ImportState> = Item::new("import_state");
 pub const GLOBAL_BALANCE_FREEZE: Item<bool> = Item::new("global_balance_freeze");
 pub const FROZEN_ACCOUNTS: Map<&Addr, bool> = Map::new("frozen_accounts");
-pub const BALANCE_COPY_CURSORS: Map<&Addr, String> = Map::new("balance_copy_cursors");
-pub const BALANCE_CHANGE_LISTENERS: Item<Vec<Addr>> = Item::new("balance_change_listeners");
+/// Cursor of the balance copy in progress, keyed by CopySource::key
+pub const BALANCE_COPY_CURSORS: Map<&str, String> = Map::new("balance_copy_cursors");
+/// Source, mode and running totals of the balance copy in progress
+pub const BALANCE_COPY_JOB: Item<BalanceCopyJob> = Item::new("balance_copy_job");
+/// Registered balance change listeners, with the block they were added in
+pub const BALANCE_CHANGE_LISTENERS: Map<&Addr, ListenerRegistration> = Map::new("balance_change_listener_registry");
+/// Listener addresses as stored before the registry was keyed by address. Only
+/// read when migrating.
+pub const LEGACY_BALANCE_CHANGE_LISTENERS: Item<Vec<Addr>> = Item::new("balance_change_listeners");
+/// Event filter and delivery settings for balance change listeners
+pub const BALANCE_CHANGE_LISTENER_CONFIGS: Map<&Addr, BalanceChangeListenerConfig> =
+    Map::new("balance_change_listener_configs");
+/// Delivery failure tracking for balance change listeners
+pub const LISTENER_HEALTH: Map<&Addr, ListenerHealth> = Map::new("listener_health");
+/// Listener, call number and event of recent listener submsgs, by reply ID.
+/// Entries are overwritten as reply IDs are reused rather than removed after
+/// each call.
+pub const LISTENER_DELIVERIES: Map<u64, (Addr, u32, QueuedBalanceChangeEvent)> = Map::new("listener_deliveries");
+/// Number of listener reply IDs assigned so far, offset by the first ID
+pub const LISTENER_REPLY_ID_COUNTER: Item<u64> = Item::new("listener_reply_id_counter");
+/// Events that failed to be delivered, by listener and queue sequence number
+pub const LISTENER_RETRY_QUEUE: Map<(&Addr, u64), QueuedBalanceChangeEvent> = Map::new("listener_retry_queue");
+/// Sequence number of the next event added to each listener's retry queue
+pub const LISTENER_RETRY_SEQS: Map<&Addr, u64> = Map::new("listener_retry_seqs");
+
+/// Upper bound on the number of addresses watched by a listener filter
+pub const MAX_FILTER_ADDRESSES: usize = 50;
+/// Gas limit for each call to a registered listener, unless its config sets one
+pub const DEFAULT_LISTENER_GAS_LIMIT: u64 = 1_000_000;
+/// Consecutive failures after which a listener is disabled, unless overridden
+pub const DEFAULT_MAX_LISTENER_FAILURES: u32 = 5;
+/// Max number of events kept in each listener's retry queue
+pub const MAX_LISTENER_RETRY_QUEUE_SIZE: u64 = 1_000;
+
+/// Self-service subscriptions to balance change events, by subscriber
+pub const SUBSCRIPTIONS: Map<&Addr, Subscription> = Map::new("subscriptions");
+/// Index of subscribers by watched address
+pub const WATCHERS: Map<(&Addr, &Addr), u8> = Map::new("watchers");
+/// Subscribers evicted from watching an address, by watched address and
+/// subscriber, with the block they were evicted in. They can't watch it again
+/// until the watched account allows them.
+pub const EVICTED_SUBSCRIBERS: Map<(&Addr, &Addr), u64> = Map::new("evicted_subscribers");
+/// Deposit required to create a new subscription, if any
+pub const SUBSCRIPTION_DEPOSIT: Item<Coin> = Item::new("subscription_deposit");
+
+/// Max number of addresses a single subscriber can watch
+pub const MAX_SUBSCRIPTION_ADDRESSES: usize = 25;
+/// Max number of subscribers watching any single address
+pub const MAX_SUBSCRIBERS_PER_ADDRESS: usize = 10;
+/// Max gas a subscriber's callback can use
+pub const MAX_SUBSCRIBER_GAS_LIMIT: u64 = 500_000;
+/// Accounts, like treasuries, vesting contracts and LP pools, that are left out
+/// of ranked balance queries, holder counts and the circulating supply
+pub const EXCLUDED_ACCOUNTS: Map<&Addr, bool> = Map::new("excluded_accounts");
+
+/// Upper bound on the size of the EXCLUDED_ACCOUNTS registry
+pub const MAX_EXCLUDED_ACCOUNTS: usize = 100;
+
+/// Legacy CW20s that can be swapped for this token through Receive
+pub const LEGACY_TOKENS: Map<&Addr, LegacyToken> = Map::new("legacy_tokens");
+
+/// Upper bound on the number of accepted legacy tokens
+pub const MAX_LEGACY_TOKENS: usize = 20;
+
+/// Holder tiers, sorted in ascending order of min_balance
+pub const TIERS: Item<Vec<HolderTier>> = Item::new("tiers");
+/// Number of holders whose balance currently falls in each tier, by tier name
+pub const N_TIER_HOLDERS: Map<&str, Uint64> = Map::new("n_tier_holders");
+
+/// Upper bound on the number of configurable holder tiers
+pub const MAX_TIERS: usize = 20;
+
+/// Sum of the squares of all balances, used to compute the HHI
+pub const SUM_SQUARED_BALANCES: Item<Uint256> = Item::new("sum_squared_balances");
+/// Number of holders and their total balance, bucketed by floor(log10(balance))
+pub const BALANCE_HISTOGRAM: Map<u8, (Uint64, Uint128)> = Map::new("balance_histogram");
+
+/// Daily holder count, supply and transfer activity snapshots, by day index
+pub const HISTORY: Map<u64, HistorySnapshot> = Map::new("history");
+
+/// Number of days of snapshots retained in HISTORY
+pub const MAX_HISTORY_DAYS: u64 = 730;
+
+/// Lifetime transfer, mint and burn totals per account
+pub const ACCOUNT_STATS: Map<&Addr, AccountStats> = Map::new("account_stats");
+/// Index for paginating accounts by number of transfers sent
+pub const ORDERED_SENDERS: Map<(u64, &Addr), u8> = Map::new("ordered_senders");
+
+/// Number of recent transfers kept per account. Zero or unset means disabled.
+pub const TRANSFER_HISTORY_SIZE: Item<u16> = Item::new("transfer_history_size");
+/// Recent transfers per account, keyed by a per-account sequence number
+pub const TRANSFER_HISTORY: Map<(&Addr, u32), TransferRecord> = Map::new("transfer_history");
+/// Sequence number of the next transfer recorded for each account
+pub const TRANSFER_HISTORY_SEQS: Map<&Addr, u32> = Map::new("transfer_history_seqs");
+
+/// Upper bound on the configurable per-account transfer history size
+pub const MAX_TRANSFER_HISTORY_SIZE: u16 = 100;
 
 /// Top-level initialization of contract state
 pub fn init(
@@ -52,6 +160,7 @@ pub fn init(
             if !amount.is_zero() {
                 let address = Addr::unchecked(address);
                 ORDERED_BALANCES.save(deps.storage, (amount.u128(), &address), &0)?;
+                update_balance_stats(deps.storage, Uint128::zero(), *amount)?;
                 non_zero_initial_balances.push(Cw20Coin {
                     address: address.to_string(),
                     amount: *amount,
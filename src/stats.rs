@@ -0,0 +1,59 @@
+use cosmwasm_std::{Storage, Uint128, Uint256};
+
+use crate::{
+    error::ContractError,
+    math::{add_u128, add_u256, add_u64, mul_u256},
+    state::{BALANCE_HISTOGRAM, SUM_SQUARED_BALANCES},
+};
+
+/// Index of the log10-scale histogram bucket that the balance falls in
+pub fn histogram_bucket(balance: Uint128) -> u8 {
+    balance.u128().checked_ilog10().unwrap_or_default() as u8
+}
+
+/// Incrementally maintain the distribution statistics that are too expensive
+/// to compute at query time: the sum of squared balances, used to compute the
+/// HHI, and the log-scale balance histogram. Decrements saturate at zero so
+/// that stats which were never initialized, as on a contract migrated from
+/// before they existed, can't block transfers. A Reindex makes them exact.
+pub fn update_balance_stats(
+    store: &mut dyn Storage,
+    prev_balance: Uint128,
+    next_balance: Uint128,
+) -> Result<(), ContractError> {
+    if prev_balance == next_balance {
+        return Ok(());
+    }
+
+    let sum_squares = load_sum_squared_balances(store)?.saturating_sub(mul_u256(prev_balance, prev_balance)?);
+    SUM_SQUARED_BALANCES.save(store, &add_u256(sum_squares, mul_u256(next_balance, next_balance)?)?)?;
+
+    if !prev_balance.is_zero() {
+        BALANCE_HISTOGRAM.update(
+            store,
+            histogram_bucket(prev_balance),
+            |bucket| -> Result<_, ContractError> {
+                let (n, amount) = bucket.unwrap_or_default();
+                Ok((n.saturating_sub(1u64.into()), amount.saturating_sub(prev_balance)))
+            },
+        )?;
+    }
+
+    if !next_balance.is_zero() {
+        BALANCE_HISTOGRAM.update(
+            store,
+            histogram_bucket(next_balance),
+            |bucket| -> Result<_, ContractError> {
+                let (n, amount) = bucket.unwrap_or_default();
+                Ok((add_u64(n, 1u64)?, add_u128(amount, next_balance)?))
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Sum of squared balances, or zero if never initialized
+pub fn load_sum_squared_balances(store: &dyn Storage) -> Result<Uint256, ContractError> {
+    Ok(SUM_SQUARED_BALANCES.may_load(store)?.unwrap_or_default())
+}
@@ -0,0 +1,109 @@
+use osmosis_std_derive::CosmwasmExt;
+
+use crate::tf::cosmos::common::Coin;
+
+// see https://github.com/cosmos/cosmos-sdk/blob/v0.47.5/proto/cosmos/bank/v1beta1/query.proto
+
+/// PageRequest is to be embedded in gRPC request messages for efficient
+/// pagination.
+#[derive(
+    Clone, PartialEq, Eq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize, schemars::JsonSchema, CosmwasmExt,
+)]
+#[proto_message(type_url = "/cosmos.base.query.v1beta1.PageRequest")]
+pub struct PageRequest {
+    /// key is a value returned in PageResponse.next_key to begin querying the
+    /// next page most efficiently.
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+    #[prost(uint64, tag = "3")]
+    pub limit: u64,
+    #[prost(bool, tag = "4")]
+    pub count_total: bool,
+    #[prost(bool, tag = "5")]
+    pub reverse: bool,
+}
+
+/// PageResponse is to be embedded in gRPC response messages where the
+/// corresponding request message has used PageRequest. The total field is
+/// omitted, as it's JSON-encoded as a string.
+#[derive(
+    Clone, PartialEq, Eq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize, schemars::JsonSchema, CosmwasmExt,
+)]
+#[proto_message(type_url = "/cosmos.base.query.v1beta1.PageResponse")]
+pub struct PageResponse {
+    /// next_key is the key to be passed to PageRequest.key to query the next
+    /// page. It's empty if there are no more results.
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(
+        default,
+        serialize_with = "base64_bytes::serialize",
+        deserialize_with = "base64_bytes::deserialize"
+    )]
+    #[schemars(with = "String")]
+    pub next_key: ::prost::alloc::vec::Vec<u8>,
+}
+
+/// DenomOwner defines structure representing an account that owns or holds a
+/// particular denominated token.
+#[derive(
+    Clone, PartialEq, Eq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize, schemars::JsonSchema, CosmwasmExt,
+)]
+#[proto_message(type_url = "/cosmos.bank.v1beta1.DenomOwner")]
+pub struct DenomOwner {
+    #[prost(string, tag = "1")]
+    pub address: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub balance: ::core::option::Option<Coin>,
+}
+
+/// QueryDenomOwnersRequest defines the request type for the DenomOwners RPC
+/// query, which queries for a paginated set of all account holders of a
+/// particular denomination.
+#[derive(
+    Clone, PartialEq, Eq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize, schemars::JsonSchema, CosmwasmExt,
+)]
+#[proto_message(type_url = "/cosmos.bank.v1beta1.QueryDenomOwnersRequest")]
+#[proto_query(
+    path = "/cosmos.bank.v1beta1.Query/DenomOwners",
+    response_type = QueryDenomOwnersResponse
+)]
+pub struct QueryDenomOwnersRequest {
+    #[prost(string, tag = "1")]
+    pub denom: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: ::core::option::Option<PageRequest>,
+}
+
+/// QueryDenomOwnersResponse defines the RPC response of a DenomOwners RPC
+/// query.
+#[derive(
+    Clone, PartialEq, Eq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize, schemars::JsonSchema, CosmwasmExt,
+)]
+#[proto_message(type_url = "/cosmos.bank.v1beta1.QueryDenomOwnersResponse")]
+pub struct QueryDenomOwnersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub denom_owners: ::prost::alloc::vec::Vec<DenomOwner>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: ::core::option::Option<PageResponse>,
+}
+
+/// Proto JSON encodes bytes fields as base64 strings
+mod base64_bytes {
+    use cosmwasm_std::Binary;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &[u8],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Binary::from(bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        Ok(Option::<Binary>::deserialize(deserializer)?
+            .map(|b| b.to_vec())
+            .unwrap_or_default())
+    }
+}
@@ -46,8 +46,8 @@ impl FromStr for TokenFactoryType {
     }
 }
 impl TokenFactoryType {
-    pub fn from_chain_id(chain_id: &String) -> Self {
-        match chain_id.as_str() {
+    pub fn from_chain_id(chain_id: &str) -> Self {
+        match chain_id {
             "juno-1" | "testing" => Self::Juno,
             "osmosis-1" | "stargaze-1" => Self::Osmosis,
             "injective-1" => Self::Injective,
@@ -0,0 +1,64 @@
+use cosmwasm_std::{Addr, Storage, Uint128};
+
+use crate::{
+    error::ContractError,
+    math::{add_u64, sub_u64},
+    msg::{BalanceChangeEvent, HolderTier},
+    state::{N_TIER_HOLDERS, TIERS},
+};
+
+/// Return the highest tier whose threshold the given balance meets. Tiers are
+/// stored in ascending order of min_balance.
+pub fn find_tier(
+    tiers: &[HolderTier],
+    balance: Uint128,
+) -> Option<&HolderTier> {
+    if balance.is_zero() {
+        return None;
+    }
+    tiers.iter().rev().find(|t| balance >= t.min_balance)
+}
+
+/// Return the exclusive upper amount bound of the tier at the given index, or
+/// None for the top tier.
+pub fn tier_upper_bound(
+    tiers: &[HolderTier],
+    index: usize,
+) -> Option<Uint128> {
+    tiers.get(index + 1).map(|t| t.min_balance)
+}
+
+/// Move the account between per-tier holder counts if its balance change
+/// crosses a tier threshold, returning a TierChanged event when it does.
+pub fn update_holder_tier(
+    store: &mut dyn Storage,
+    address: &Addr,
+    prev_balance: Uint128,
+    next_balance: Uint128,
+) -> Result<Option<BalanceChangeEvent>, ContractError> {
+    let tiers = match TIERS.may_load(store)? {
+        Some(tiers) if !tiers.is_empty() => tiers,
+        _ => return Ok(None),
+    };
+
+    let prev_tier = find_tier(&tiers, prev_balance).map(|t| t.name.to_owned());
+    let next_tier = find_tier(&tiers, next_balance).map(|t| t.name.to_owned());
+
+    if prev_tier == next_tier {
+        return Ok(None);
+    }
+
+    if let Some(name) = &prev_tier {
+        N_TIER_HOLDERS.update(store, name, |n| sub_u64(n.unwrap_or_default(), 1u64))?;
+    }
+    if let Some(name) = &next_tier {
+        N_TIER_HOLDERS.update(store, name, |n| add_u64(n.unwrap_or_default(), 1u64))?;
+    }
+
+    Ok(Some(BalanceChangeEvent::TierChanged {
+        address: address.to_owned(),
+        balance: next_balance,
+        prev_tier,
+        tier: next_tier,
+    }))
+}
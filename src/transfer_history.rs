@@ -0,0 +1,77 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{Addr, Env, Int256, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    msg::TransferRecord,
+    state::{TRANSFER_HISTORY, TRANSFER_HISTORY_SEQS, TRANSFER_HISTORY_SIZE},
+};
+
+/// Append the transfer to the sender's and recipient's recent transfer
+/// buffers, evicting every entry that has fallen out of them. Besides the
+/// oldest entry of a full buffer, this includes entries left behind when the
+/// operator has since reduced the buffer size, or set it to zero.
+pub fn record_transfer_history(
+    store: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+    memo: &Option<String>,
+) -> Result<(), ContractError> {
+    let size = TRANSFER_HISTORY_SIZE.may_load(store)?.unwrap_or_default() as u32;
+
+    for (address, counterparty, amount) in [
+        (sender, recipient, -Int256::from(amount)),
+        (recipient, sender, Int256::from(amount)),
+    ] {
+        let seq = match TRANSFER_HISTORY_SEQS.may_load(store, address)? {
+            Some(seq) => seq,
+            None if size == 0 => continue,
+            None => 0,
+        };
+        if size == 0 {
+            evict_transfer_history(store, address, seq)?;
+            continue;
+        }
+        TRANSFER_HISTORY.save(
+            store,
+            (address, seq),
+            &TransferRecord {
+                counterparty: counterparty.to_owned(),
+                amount,
+                height: env.block.height,
+                time: env.block.time,
+                memo: memo.to_owned(),
+            },
+        )?;
+        evict_transfer_history(store, address, (seq + 1).saturating_sub(size))?;
+        TRANSFER_HISTORY_SEQS.save(store, address, &(seq + 1))?;
+    }
+
+    Ok(())
+}
+
+/// Remove the account's entries with a seq below `min_seq`. Since the buffer
+/// size is capped, there are at most MAX_TRANSFER_HISTORY_SIZE of them.
+fn evict_transfer_history(
+    store: &mut dyn Storage,
+    address: &Addr,
+    min_seq: u32,
+) -> Result<(), ContractError> {
+    let seqs = TRANSFER_HISTORY
+        .prefix(address)
+        .keys(
+            store,
+            None,
+            Some(Bound::Exclusive((min_seq, PhantomData))),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for seq in seqs {
+        TRANSFER_HISTORY.remove(store, (address, seq));
+    }
+    Ok(())
+}
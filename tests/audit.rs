@@ -0,0 +1,101 @@
+mod common;
+
+use common::{query_pro, setup, transfer, Deps};
+use cosmwasm_std::{Addr, StdResult, Uint128, Uint64};
+use cw20_base::state::TOKEN_INFO;
+use cw20_pro::{
+    msg::{AuditMismatch, AuditResponse, ProQueryMsg},
+    state::{N_BALANCES, ORDERED_BALANCES},
+};
+
+/// Page through a full audit, returning every mismatch and the number of pages
+fn audit(
+    deps: &Deps,
+    limit: u16,
+) -> (Vec<AuditMismatch>, usize) {
+    let mut mismatches = vec![];
+    let mut cursor = None;
+    let mut n_pages = 0;
+    loop {
+        let resp: AuditResponse = query_pro(
+            deps,
+            ProQueryMsg::Audit {
+                limit: Some(limit),
+                cursor,
+            },
+        )
+        .unwrap();
+        n_pages += 1;
+        mismatches.extend(resp.mismatches);
+        if resp.done {
+            assert!(resp.cursor.is_none());
+            return (mismatches, n_pages);
+        }
+        cursor = resp.cursor;
+    }
+}
+
+#[test]
+fn audit_of_a_consistent_contract_is_clean() {
+    let mut deps = setup(&[("alice", 50), ("bob", 40), ("carol", 30)]);
+    transfer(&mut deps, "alice", "dave", 20);
+    transfer(&mut deps, "carol", "bob", 30);
+
+    let (mismatches, n_pages) = audit(&deps, 2);
+    assert!(mismatches.is_empty());
+    assert!(n_pages > 2);
+}
+
+#[test]
+fn audit_finds_drift_across_pages() {
+    let mut deps = setup(&[("alice", 50), ("bob", 40), ("carol", 30)]);
+
+    ORDERED_BALANCES.remove(&mut deps.storage, (50, &Addr::unchecked("alice")));
+    ORDERED_BALANCES
+        .save(&mut deps.storage, (7, &Addr::unchecked("ghost")), &0)
+        .unwrap();
+    ORDERED_BALANCES.remove(&mut deps.storage, (40, &Addr::unchecked("bob")));
+    ORDERED_BALANCES
+        .save(&mut deps.storage, (45, &Addr::unchecked("bob")), &0)
+        .unwrap();
+    N_BALANCES.save(&mut deps.storage, &Uint64::new(9)).unwrap();
+    TOKEN_INFO
+        .update(&mut deps.storage, |mut info| -> StdResult<_> {
+            info.total_supply = Uint128::new(1_000);
+            Ok(info)
+        })
+        .unwrap();
+
+    let (mismatches, _) = audit(&deps, 1);
+    assert_eq!(
+        mismatches,
+        vec![
+            AuditMismatch::MissingIndexEntry {
+                address: Addr::unchecked("alice"),
+                balance: Uint128::new(50),
+            },
+            AuditMismatch::MissingIndexEntry {
+                address: Addr::unchecked("bob"),
+                balance: Uint128::new(40),
+            },
+            AuditMismatch::StaleIndexEntry {
+                address: Addr::unchecked("ghost"),
+                amount: Uint128::new(7),
+                balance: Uint128::zero(),
+            },
+            AuditMismatch::StaleIndexEntry {
+                address: Addr::unchecked("bob"),
+                amount: Uint128::new(45),
+                balance: Uint128::new(40),
+            },
+            AuditMismatch::HolderCount {
+                expected: Uint64::new(3),
+                actual: Uint64::new(9),
+            },
+            AuditMismatch::TotalSupply {
+                expected: Uint128::new(120),
+                actual: Uint128::new(1_000),
+            },
+        ]
+    );
+}
@@ -0,0 +1,118 @@
+#![allow(dead_code)]
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_json, to_json_binary, ContractInfoResponse, ContractResult, Empty, Env, OwnedDeps, Response, SystemResult,
+    Uint128, WasmQuery,
+};
+use cw20::Cw20Coin;
+use cw20_pro::{
+    contract::{execute, instantiate, query},
+    error::ContractError,
+    msg::{ExecuteMsg, OperatorExecuteMsg, ProQueryMsg, QueryMsg},
+};
+use serde::de::DeserializeOwned;
+
+pub type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>;
+
+pub const OPERATOR: &str = "operator";
+
+/// Instantiate a token with the given balances, operated by OPERATOR
+pub fn setup(balances: &[(&str, u128)]) -> Deps {
+    let mut deps = mock_dependencies();
+    // Every address is a contract, so that it can subscribe
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::ContractInfo { .. } => SystemResult::Ok(ContractResult::Ok(
+            to_json_binary(&ContractInfoResponse::default()).unwrap(),
+        )),
+        _ => panic!("unexpected query"),
+    });
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OPERATOR, &[]),
+        cw20_base::msg::InstantiateMsg {
+            name: "Token".to_owned(),
+            symbol: "TKN".to_owned(),
+            decimals: 6,
+            initial_balances: balances
+                .iter()
+                .map(|(address, amount)| Cw20Coin {
+                    address: address.to_string(),
+                    amount: Uint128::new(*amount),
+                })
+                .collect(),
+            mint: None,
+            marketing: None,
+        },
+    )
+    .unwrap();
+    deps
+}
+
+pub fn execute_as(
+    deps: &mut Deps,
+    env: Env,
+    sender: &str,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+}
+
+pub fn operator_execute(
+    deps: &mut Deps,
+    msg: OperatorExecuteMsg,
+) -> Result<Response, ContractError> {
+    execute_as(deps, mock_env(), OPERATOR, ExecuteMsg::Pro(msg))
+}
+
+pub fn transfer(
+    deps: &mut Deps,
+    sender: &str,
+    recipient: &str,
+    amount: u128,
+) {
+    execute_as(
+        deps,
+        mock_env(),
+        sender,
+        ExecuteMsg::Transfer {
+            recipient: recipient.to_owned(),
+            amount: Uint128::new(amount),
+            memo: None,
+        },
+    )
+    .unwrap();
+}
+
+pub fn query_pro<T: DeserializeOwned>(
+    deps: &Deps,
+    msg: ProQueryMsg,
+) -> Result<T, ContractError> {
+    Ok(from_json(query(deps.as_ref(), mock_env(), QueryMsg::Pro(msg))?)?)
+}
+
+/// Whether the response has a "done" attribute set to true
+pub fn is_done(resp: &Response) -> bool {
+    resp.events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .chain(resp.attributes.iter())
+        .any(|a| a.key == "done" && a.value == "true")
+}
+
+/// Run a Reindex to completion, `limit` entries per call, returning the
+/// number of calls it took
+pub fn reindex(
+    deps: &mut Deps,
+    limit: u32,
+) -> usize {
+    let mut n_calls = 0;
+    loop {
+        n_calls += 1;
+        let resp = operator_execute(deps, OperatorExecuteMsg::Reindex { limit: Some(limit) }).unwrap();
+        if is_done(&resp) {
+            return n_calls;
+        }
+    }
+}
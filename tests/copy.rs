@@ -0,0 +1,254 @@
+use cosmwasm_std::{Addr, StdResult, Uint128};
+use cw20::{Cw20Coin, MinterResponse, TokenInfoResponse};
+use cw20_pro::{
+    contract::{execute, instantiate, query, reply},
+    msg::{
+        BalanceCopyMode, CopyCapBehavior, CopyJobResponse, CopyPreviewResponse, CopySource, CopySourceKind, ExecuteMsg,
+        OperatorExecuteMsg, ProQueryMsg, QueryMsg,
+    },
+};
+use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
+
+const OPERATOR: &str = "operator";
+const N_HOLDERS: u128 = 80;
+const CAP: u128 = 6_500;
+
+struct Suite {
+    app: App,
+    source: Addr,
+    token: Addr,
+}
+
+impl Suite {
+    /// A plain CW20 with more holders than fit in one copy batch, and a
+    /// cw20-pro whose cap can't fit all of their balances
+    fn new() -> Self {
+        let mut app = App::default();
+        let source_code_id = app.store_code(Box::new(ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        )));
+        let token_code_id = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+        ));
+
+        let source = app
+            .instantiate_contract(
+                source_code_id,
+                Addr::unchecked(OPERATOR),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Legacy".to_owned(),
+                    symbol: "LGC".to_owned(),
+                    decimals: 6,
+                    initial_balances: (0..N_HOLDERS)
+                        .map(|i| Cw20Coin {
+                            address: format!("holder{:02}", i),
+                            amount: Uint128::new(100),
+                        })
+                        .collect(),
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "source",
+                None,
+            )
+            .unwrap();
+        let token = app
+            .instantiate_contract(
+                token_code_id,
+                Addr::unchecked(OPERATOR),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Token".to_owned(),
+                    symbol: "TKN".to_owned(),
+                    decimals: 6,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: OPERATOR.to_owned(),
+                        cap: Some(Uint128::new(CAP)),
+                    }),
+                    marketing: None,
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+
+        Self { app, source, token }
+    }
+
+    fn copy(
+        &mut self,
+        cap_behavior: Option<CopyCapBehavior>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(OPERATOR),
+            self.token.to_owned(),
+            &ExecuteMsg::Pro(OperatorExecuteMsg::CopyBalances {
+                source: CopySource::Cw20 {
+                    address: self.source.to_owned(),
+                },
+                mode: BalanceCopyMode::Replace,
+                cap_behavior,
+                remap_prefix: None,
+            }),
+            &[],
+        )
+    }
+
+    fn preview(
+        &self,
+        mode: BalanceCopyMode,
+        cap_behavior: Option<CopyCapBehavior>,
+        remap_prefix: Option<bool>,
+    ) -> StdResult<CopyPreviewResponse> {
+        self.app.wrap().query_wasm_smart(
+            self.token.to_owned(),
+            &QueryMsg::Pro(ProQueryMsg::CopyPreview {
+                source: CopySource::Cw20 {
+                    address: self.source.to_owned(),
+                },
+                mode,
+                cap_behavior,
+                remap_prefix,
+            }),
+        )
+    }
+
+    fn copy_job(&self) -> CopyJobResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.token.to_owned(), &QueryMsg::Pro(ProQueryMsg::CopyJob {}))
+            .unwrap()
+    }
+
+    fn total_supply(&self) -> Uint128 {
+        let resp: TokenInfoResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.token.to_owned(), &QueryMsg::TokenInfo {})
+            .unwrap();
+        resp.total_supply
+    }
+}
+
+fn is_done(resp: &AppResponse) -> bool {
+    resp.events
+        .iter()
+        .flat_map(|e| e.attributes.iter())
+        .any(|a| a.key == "done" && a.value == "true")
+}
+
+#[test]
+fn continuing_copy_keeps_its_cap_behavior() {
+    let mut suite = Suite::new();
+
+    let resp = suite.copy(Some(CopyCapBehavior::Truncate)).unwrap();
+    assert!(!is_done(&resp));
+    let job = suite.copy_job();
+    assert_eq!(job.source, Some(suite.source.to_string()));
+    assert_eq!(job.job.unwrap().cap_behavior, CopyCapBehavior::Truncate);
+
+    // A different cap behavior can't be mixed into the job
+    suite.copy(Some(CopyCapBehavior::Fail)).unwrap_err();
+
+    // Leaving it out continues with the job's, truncating at the cap instead
+    // of failing
+    let resp = suite.copy(None).unwrap();
+    assert!(is_done(&resp));
+    assert_eq!(suite.total_supply(), Uint128::new(CAP));
+
+    let job = suite.copy_job();
+    assert!(job.source.is_none() && job.job.is_none());
+}
+
+#[test]
+fn preview_follows_the_copy_in_progress() {
+    let mut suite = Suite::new();
+    suite.copy(Some(CopyCapBehavior::Truncate)).unwrap();
+
+    // Without a cap behavior, the preview truncates like the job will
+    let preview = suite.preview(BalanceCopyMode::Replace, None, None).unwrap();
+    assert!(preview.done);
+    assert_eq!(preview.total_supply, Uint128::new(CAP));
+
+    // Settings the job would reject are rejected by the preview too
+    suite
+        .preview(BalanceCopyMode::Replace, Some(CopyCapBehavior::Fail), None)
+        .unwrap_err();
+    suite.preview(BalanceCopyMode::Increment, None, None).unwrap_err();
+    suite.preview(BalanceCopyMode::Replace, None, Some(true)).unwrap_err();
+
+    // The preview matches what the continuation then does
+    let resp = suite.copy(None).unwrap();
+    assert!(is_done(&resp));
+    assert_eq!(suite.total_supply(), preview.total_supply);
+}
+
+#[test]
+fn cw20_pro_source_being_reindexed_is_reported() {
+    let mut suite = Suite::new();
+    let pro_code_id = suite.app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+    ));
+    suite.source = suite
+        .app
+        .instantiate_contract(
+            pro_code_id,
+            Addr::unchecked(OPERATOR),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Pro".to_owned(),
+                symbol: "PRO".to_owned(),
+                decimals: 6,
+                initial_balances: (0..3)
+                    .map(|i| Cw20Coin {
+                        address: format!("holder{:02}", i),
+                        amount: Uint128::new(100),
+                    })
+                    .collect(),
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "pro",
+            None,
+        )
+        .unwrap();
+    let reindex = ExecuteMsg::Pro(OperatorExecuteMsg::Reindex { limit: Some(1) });
+    suite
+        .app
+        .execute_contract(Addr::unchecked(OPERATOR), suite.source.to_owned(), &reindex, &[])
+        .unwrap();
+
+    // The ranked balances query is blocked mid-Reindex, which mustn't be
+    // mistaken for a plain CW20
+    let err = suite.preview(BalanceCopyMode::Replace, None, None).unwrap_err();
+    assert!(err.to_string().contains("Reindex"), "{}", err);
+    suite.copy(None).unwrap_err();
+
+    loop {
+        let resp = suite
+            .app
+            .execute_contract(Addr::unchecked(OPERATOR), suite.source.to_owned(), &reindex, &[])
+            .unwrap();
+        if is_done(&resp) {
+            break;
+        }
+    }
+
+    let preview = suite.preview(BalanceCopyMode::Replace, None, None).unwrap();
+    assert_eq!(preview.source_kind, CopySourceKind::Cw20Pro);
+    assert_eq!(preview.total_supply, Uint128::new(300));
+}
+
+#[test]
+fn copy_fails_at_the_cap_by_default() {
+    let mut suite = Suite::new();
+
+    suite.copy(None).unwrap();
+    suite.copy(None).unwrap_err();
+    suite.copy(Some(CopyCapBehavior::Truncate)).unwrap_err();
+    assert_eq!(suite.total_supply(), Uint128::new(5_000));
+}
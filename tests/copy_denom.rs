@@ -0,0 +1,255 @@
+mod common;
+
+use std::marker::PhantomData;
+
+use bech32::{ToBase32, Variant};
+use common::{is_done, OPERATOR};
+use cosmwasm_std::{
+    from_json,
+    testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    to_json_binary, ContractResult, Empty, Env, OwnedDeps, Querier, QuerierResult, QueryRequest, Response,
+    SystemResult, Uint128,
+};
+use cw20::{BalanceResponse, TokenInfoResponse};
+use cw20_pro::{
+    contract::{execute, instantiate, query},
+    error::ContractError,
+    msg::{BalanceCopyMode, CopyPreviewResponse, CopySource, ExecuteMsg, OperatorExecuteMsg, ProQueryMsg, QueryMsg},
+    tf::cosmos::{
+        bank::{DenomOwner, PageResponse, QueryDenomOwnersRequest, QueryDenomOwnersResponse},
+        common::Coin,
+    },
+};
+
+const DENOM: &str = "uatom";
+
+/// Answers DenomOwners queries for DENOM from a fixed list of owners, paging
+/// by the last address returned like the bank module does
+#[derive(Default)]
+struct DenomQuerier {
+    base: MockQuerier,
+    owners: Vec<(String, u128)>,
+}
+
+impl DenomQuerier {
+    fn denom_owners(
+        &self,
+        request: QueryDenomOwnersRequest,
+    ) -> QueryDenomOwnersResponse {
+        assert_eq!(request.denom, DENOM);
+        let page = request.pagination.unwrap();
+        let start = match page.key.is_empty() {
+            true => 0,
+            false => {
+                let start_after = String::from_utf8(page.key).unwrap();
+                self.owners.iter().position(|(a, _)| *a == start_after).unwrap() + 1
+            },
+        };
+        let end = (start + page.limit as usize).min(self.owners.len());
+        let next_key = match end < self.owners.len() {
+            true => self.owners[end - 1].0.as_bytes().to_vec(),
+            false => vec![],
+        };
+        QueryDenomOwnersResponse {
+            denom_owners: self.owners[start..end]
+                .iter()
+                .map(|(address, amount)| DenomOwner {
+                    address: address.to_owned(),
+                    balance: Some(Coin {
+                        denom: DENOM.to_owned(),
+                        amount: amount.to_string(),
+                    }),
+                })
+                .collect(),
+            pagination: Some(PageResponse { next_key }),
+        }
+    }
+}
+
+impl Querier for DenomQuerier {
+    fn raw_query(
+        &self,
+        bin_request: &[u8],
+    ) -> QuerierResult {
+        match from_json::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Stargate { path, data }) if path == "/cosmos.bank.v1beta1.Query/DenomOwners" => {
+                let request = QueryDenomOwnersRequest::try_from(data).unwrap();
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&self.denom_owners(request)).unwrap()))
+            },
+            _ => self.base.raw_query(bin_request),
+        }
+    }
+}
+
+type Deps = OwnedDeps<MockStorage, MockApi, DenomQuerier, Empty>;
+
+/// A token with 6 decimals and no balances, for DENOM to be copied into
+fn setup(owners: Vec<(String, u128)>) -> Deps {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: DenomQuerier {
+            owners,
+            ..Default::default()
+        },
+        custom_query_type: PhantomData,
+    };
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OPERATOR, &[]),
+        cw20_base::msg::InstantiateMsg {
+            name: "Token".to_owned(),
+            symbol: "TKN".to_owned(),
+            decimals: 6,
+            initial_balances: vec![],
+            mint: None,
+            marketing: None,
+        },
+    )
+    .unwrap();
+    deps
+}
+
+fn copy_denom(
+    deps: &mut Deps,
+    env: Env,
+    exponent: Option<u8>,
+    remap_prefix: Option<bool>,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(OPERATOR, &[]),
+        ExecuteMsg::Pro(OperatorExecuteMsg::CopyBalances {
+            source: CopySource::Denom {
+                denom: DENOM.to_owned(),
+                exponent,
+            },
+            mode: BalanceCopyMode::Replace,
+            cap_behavior: None,
+            remap_prefix,
+        }),
+    )
+}
+
+/// Env of a contract with a bech32 address, for remapping to its prefix
+fn bech32_env() -> Env {
+    let mut env = mock_env();
+    env.contract.address = MockApi::default().addr_make("token");
+    env
+}
+
+fn bech32_address(
+    prefix: &str,
+    bytes: &[u8],
+) -> String {
+    bech32::encode(prefix, bytes.to_base32(), Variant::Bech32).unwrap()
+}
+
+fn balance(
+    deps: &Deps,
+    address: &str,
+) -> u128 {
+    let resp: BalanceResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance {
+                address: address.to_owned(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    resp.balance.u128()
+}
+
+fn total_supply(deps: &Deps) -> u128 {
+    let resp: TokenInfoResponse = from_json(query(deps.as_ref(), mock_env(), QueryMsg::TokenInfo {}).unwrap()).unwrap();
+    resp.total_supply.u128()
+}
+
+#[test]
+fn denom_import_pages_through_owners() {
+    // 150 owners of an 8 decimal denom, each with a remainder that's rounded
+    // away when converting to 6 decimals
+    let owners = (1..=150u128)
+        .map(|i| (format!("holder{:03}", i), i * 100 + 99))
+        .collect();
+    let mut deps = setup(owners);
+
+    let resp = copy_denom(&mut deps, mock_env(), Some(8), None).unwrap();
+    assert!(!is_done(&resp));
+    assert_eq!(balance(&deps, "holder100"), 100);
+    assert_eq!(balance(&deps, "holder101"), 0);
+    assert_eq!(total_supply(&deps), (1..=100).sum::<u128>());
+
+    let resp = copy_denom(&mut deps, mock_env(), Some(8), None).unwrap();
+    assert!(is_done(&resp));
+    assert_eq!(balance(&deps, "holder001"), 1);
+    assert_eq!(balance(&deps, "holder150"), 150);
+    assert_eq!(total_supply(&deps), (1..=150).sum::<u128>());
+}
+
+#[test]
+fn denom_import_scales_amounts_to_token_decimals() {
+    let owners = vec![("alice".to_owned(), 5), ("bob".to_owned(), 1_234)];
+
+    // 2 decimals up to 6
+    let mut deps = setup(owners.clone());
+    assert!(is_done(&copy_denom(&mut deps, mock_env(), Some(2), None).unwrap()));
+    assert_eq!(balance(&deps, "alice"), 50_000);
+    assert_eq!(balance(&deps, "bob"), 12_340_000);
+
+    // Same decimals, or no exponent given, copies amounts as they are
+    for exponent in [Some(6), None] {
+        let mut deps = setup(owners.clone());
+        copy_denom(&mut deps, mock_env(), exponent, None).unwrap();
+        assert_eq!(balance(&deps, "alice"), 5);
+        assert_eq!(balance(&deps, "bob"), 1_234);
+        assert_eq!(total_supply(&deps), 1_239);
+    }
+}
+
+#[test]
+fn remapping_skips_addresses_that_are_not_20_bytes() {
+    let account = bech32_address("juno", &[7; 20]);
+    let contract = bech32_address("juno", &[9; 32]);
+    let mut deps = setup(vec![(account.to_owned(), 100), (contract.to_owned(), 200)]);
+
+    let preview: CopyPreviewResponse = from_json(
+        query(
+            deps.as_ref(),
+            bech32_env(),
+            QueryMsg::Pro(ProQueryMsg::CopyPreview {
+                source: CopySource::Denom {
+                    denom: DENOM.to_owned(),
+                    exponent: None,
+                },
+                mode: BalanceCopyMode::Replace,
+                cap_behavior: None,
+                remap_prefix: Some(true),
+            }),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let remapped = bech32_address("cosmwasm", &[7; 20]);
+    assert_eq!(preview.skipped, vec![contract.to_owned()]);
+    assert_eq!(preview.changes.len(), 1);
+    assert_eq!(preview.changes[0].address.as_str(), remapped);
+    assert_eq!(preview.total_supply, Uint128::new(100));
+
+    let resp = copy_denom(&mut deps, bech32_env(), None, Some(true)).unwrap();
+    assert!(is_done(&resp));
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|a| a.key == "skipped" && a.value == contract));
+    assert!(resp.attributes.iter().any(|a| a.key == "n_skipped" && a.value == "1"));
+    assert_eq!(balance(&deps, &remapped), 100);
+    assert_eq!(balance(&deps, &account), 0);
+    assert_eq!(balance(&deps, &contract), 0);
+    assert_eq!(total_supply(&deps), 100);
+}
@@ -0,0 +1,250 @@
+mod common;
+
+use common::{setup, Deps, OPERATOR};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{coin, coins, from_json, Addr, Uint128};
+use cw20::{BalanceResponse, Expiration};
+use cw20_pro::{
+    contract::{execute, query},
+    msg::{
+        ExecuteMsg, ExportCursor, ExportRecord, ExportResponse, OperatorExecuteMsg, ProQueryMsg, QueryMsg,
+        SubscriptionResponse,
+    },
+};
+
+const SUBSCRIBER: &str = "subscriber";
+
+fn export_page(
+    deps: &Deps,
+    cursor: Option<ExportCursor>,
+) -> ExportResponse {
+    from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pro(ProQueryMsg::Export { limit: Some(3), cursor }),
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn export_all(deps: &Deps) -> Vec<ExportResponse> {
+    let mut pages = vec![export_page(deps, None)];
+    while let Some(cursor) = pages.last().unwrap().cursor.to_owned() {
+        pages.push(export_page(deps, Some(cursor)));
+    }
+    pages
+}
+
+fn page_deposits(page: &ExportResponse) -> Vec<cosmwasm_std::Coin> {
+    page.records
+        .iter()
+        .filter_map(|record| match record {
+            ExportRecord::Subscription { subscription, .. } => subscription.deposit.to_owned(),
+            _ => None,
+        })
+        .collect()
+}
+
+fn balance(
+    deps: &Deps,
+    address: &str,
+) -> Uint128 {
+    let resp: BalanceResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance {
+                address: address.to_owned(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    resp.balance
+}
+
+/// A token with balances, an allowance, transfer history and a subscription
+/// paid for with a deposit
+fn populated_source() -> Deps {
+    let mut deps = setup(&[("alice", 1_000), ("bob", 500), ("carol", 20)]);
+    for msg in [
+        OperatorExecuteMsg::SetTransferHistorySize { size: 5 },
+        OperatorExecuteMsg::SetSubscriptionDeposit {
+            deposit: Some(coin(100, "ujuno")),
+        },
+    ] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OPERATOR, &[]),
+            ExecuteMsg::Pro(msg),
+        )
+        .unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        ExecuteMsg::IncreaseAllowance {
+            spender: "bob".to_owned(),
+            amount: Uint128::new(10),
+            expires: Some(Expiration::Never {}),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        ExecuteMsg::Transfer {
+            recipient: "dave".to_owned(),
+            amount: Uint128::new(300),
+            memo: Some("hi".to_owned()),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(SUBSCRIBER, &coins(100, "ujuno")),
+        ExecuteMsg::Subscribe {
+            watched: vec![Addr::unchecked("alice")],
+            event_version: None,
+            gas_limit: 100_000,
+        },
+    )
+    .unwrap();
+    deps
+}
+
+#[test]
+fn export_import_round_trip() {
+    let source = populated_source();
+    let pages = export_all(&source);
+    assert!(pages.len() > 1);
+    assert!(pages.last().unwrap().done);
+
+    let mut target = setup(&[]);
+    for (i, page) in pages.iter().enumerate() {
+        execute(
+            target.as_mut(),
+            mock_env(),
+            mock_info(OPERATOR, &page_deposits(page)),
+            ExecuteMsg::Pro(OperatorExecuteMsg::Import {
+                records: page.records.to_owned(),
+                checksum: page.checksum.to_owned(),
+                done: i == pages.len() - 1,
+            }),
+        )
+        .unwrap();
+    }
+
+    // The imported contract exports exactly the same records
+    let reexported = export_all(&target);
+    assert_eq!(reexported.last().unwrap().checksum, pages.last().unwrap().checksum);
+    for address in ["alice", "bob", "carol", "dave"] {
+        assert_eq!(balance(&target, address), balance(&source, address));
+    }
+
+    // The subscription keeps its deposit, which is refunded on unsubscribe
+    let resp: SubscriptionResponse = from_json(
+        query(
+            target.as_ref(),
+            mock_env(),
+            QueryMsg::Pro(ProQueryMsg::Subscription {
+                subscriber: Addr::unchecked(SUBSCRIBER),
+            }),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(resp.subscription.unwrap().deposit, Some(coin(100, "ujuno")));
+    let resp = execute(
+        target.as_mut(),
+        mock_env(),
+        mock_info(SUBSCRIBER, &[]),
+        ExecuteMsg::Unsubscribe {},
+    )
+    .unwrap();
+    assert_eq!(resp.messages.len(), 1);
+}
+
+#[test]
+fn import_requires_subscription_deposits() {
+    let source = populated_source();
+    let pages = export_all(&source);
+    let (i, page) = pages
+        .iter()
+        .enumerate()
+        .find(|(_, page)| !page_deposits(page).is_empty())
+        .unwrap();
+
+    let mut target = setup(&[]);
+    for page in pages[..i].iter() {
+        execute(
+            target.as_mut(),
+            mock_env(),
+            mock_info(OPERATOR, &page_deposits(page)),
+            ExecuteMsg::Pro(OperatorExecuteMsg::Import {
+                records: page.records.to_owned(),
+                checksum: page.checksum.to_owned(),
+                done: false,
+            }),
+        )
+        .unwrap();
+    }
+
+    for funds in [vec![], coins(99, "ujuno"), coins(100, "uatom")] {
+        execute(
+            target.as_mut(),
+            mock_env(),
+            mock_info(OPERATOR, &funds),
+            ExecuteMsg::Pro(OperatorExecuteMsg::Import {
+                records: page.records.to_owned(),
+                checksum: page.checksum.to_owned(),
+                done: false,
+            }),
+        )
+        .unwrap_err();
+    }
+
+    execute(
+        target.as_mut(),
+        mock_env(),
+        mock_info(OPERATOR, &page_deposits(page)),
+        ExecuteMsg::Pro(OperatorExecuteMsg::Import {
+            records: page.records.to_owned(),
+            checksum: page.checksum.to_owned(),
+            done: false,
+        }),
+    )
+    .unwrap();
+}
+
+#[test]
+fn import_rejects_a_tampered_page() {
+    let source = populated_source();
+    let pages = export_all(&source);
+
+    let mut records = pages[0].records.to_owned();
+    for record in records.iter_mut() {
+        if let ExportRecord::Balance { amount, .. } = record {
+            *amount += Uint128::one();
+        }
+    }
+
+    let mut target = setup(&[]);
+    execute(
+        target.as_mut(),
+        mock_env(),
+        mock_info(OPERATOR, &[]),
+        ExecuteMsg::Pro(OperatorExecuteMsg::Import {
+            records,
+            checksum: pages[0].checksum.to_owned(),
+            done: false,
+        }),
+    )
+    .unwrap_err();
+}
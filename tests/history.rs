@@ -0,0 +1,123 @@
+mod common;
+
+use common::{execute_as, query_pro, setup, Deps};
+use cosmwasm_std::{testing::mock_env, Env, Timestamp, Uint128, Uint64};
+use cw20_pro::msg::{ExecuteMsg, HistoryResponse, ProQueryMsg};
+
+const DAY: u64 = 86_400;
+
+fn env_on_day(day: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(day * DAY + 3_600);
+    env
+}
+
+fn transfer_on_day(
+    deps: &mut Deps,
+    day: u64,
+    recipient: &str,
+    amount: u128,
+) {
+    execute_as(
+        deps,
+        env_on_day(day),
+        "alice",
+        ExecuteMsg::Transfer {
+            recipient: recipient.to_owned(),
+            amount: Uint128::new(amount),
+            memo: None,
+        },
+    )
+    .unwrap();
+}
+
+fn history(
+    deps: &Deps,
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: Option<u16>,
+) -> HistoryResponse {
+    query_pro(
+        deps,
+        ProQueryMsg::History {
+            from: from.map(|day| Timestamp::from_seconds(day * DAY)),
+            to: to.map(|day| Timestamp::from_seconds(day * DAY)),
+            limit,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn history_keeps_one_snapshot_per_day() {
+    let mut deps = setup(&[("alice", 1_000)]);
+
+    transfer_on_day(&mut deps, 100, "bob", 10);
+    transfer_on_day(&mut deps, 100, "carol", 20);
+    transfer_on_day(&mut deps, 101, "bob", 5);
+    execute_as(
+        &mut deps,
+        env_on_day(103),
+        "bob",
+        ExecuteMsg::Burn {
+            amount: Uint128::new(15),
+        },
+    )
+    .unwrap();
+
+    let resp = history(&deps, None, None, None);
+    assert!(resp.cursor.is_none());
+    let days: Vec<_> = resp
+        .snapshots
+        .iter()
+        .map(|s| {
+            (
+                s.time.seconds() / DAY,
+                s.n_balances,
+                s.total_supply,
+                s.transfer_count,
+                s.transfer_volume,
+            )
+        })
+        .collect();
+    assert_eq!(
+        days,
+        vec![
+            (
+                100,
+                Uint64::new(3),
+                Uint128::new(1_000),
+                Uint64::new(2),
+                Uint128::new(30)
+            ),
+            (
+                101,
+                Uint64::new(3),
+                Uint128::new(1_000),
+                Uint64::new(1),
+                Uint128::new(5)
+            ),
+            // Burns update the holder count and supply, but aren't transfers
+            (103, Uint64::new(2), Uint128::new(985), Uint64::zero(), Uint128::zero()),
+        ]
+    );
+}
+
+#[test]
+fn history_pages_between_days() {
+    let mut deps = setup(&[("alice", 1_000)]);
+    for day in 100..105 {
+        transfer_on_day(&mut deps, day, "bob", 1);
+    }
+
+    let page = history(&deps, Some(101), Some(103), Some(2));
+    assert_eq!(page.snapshots.len(), 2);
+    assert_eq!(page.snapshots[0].time.seconds() / DAY, 101);
+    let cursor = page.cursor.unwrap();
+    assert_eq!(cursor.seconds() / DAY, 103);
+
+    let page = history(&deps, Some(cursor.seconds() / DAY), Some(103), Some(2));
+    assert_eq!(page.snapshots.len(), 1);
+    assert_eq!(page.snapshots[0].time.seconds() / DAY, 103);
+    assert!(page.cursor.is_none());
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{query_pro, reindex, setup, transfer, Deps, OPERATOR};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::Addr;
+use cw2::{get_contract_version, set_contract_version};
+use cw20_pro::{
+    contract::{execute, migrate, CONTRACT_NAME},
+    msg::{ExecuteMsg, MigrateMsg, OperatorExecuteMsg, ProQueryMsg, StatsResponse},
+    state::{BALANCE_HISTOGRAM, REINDEX_STATE, SUM_SQUARED_BALANCES},
+};
+
+/// Roll the contract back to how a deployment from before 0.1.0 looks, with
+/// no balance stats
+fn make_legacy(deps: &mut Deps) {
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+    SUM_SQUARED_BALANCES.remove(deps.as_mut().storage);
+    BALANCE_HISTOGRAM.clear(deps.as_mut().storage);
+}
+
+fn empty_migrate_msg() -> MigrateMsg {
+    MigrateMsg {
+        operator: None,
+        listeners: None,
+        reindex: None,
+    }
+}
+
+fn query_stats(deps: &Deps) -> StatsResponse {
+    query_pro(deps, ProQueryMsg::Stats { include_excluded: None }).unwrap()
+}
+
+#[test]
+fn transfers_work_on_stats_that_were_never_initialized() {
+    let mut deps = setup(&[("alice", 1_000), ("bob", 50)]);
+    SUM_SQUARED_BALANCES.remove(deps.as_mut().storage);
+    BALANCE_HISTOGRAM.clear(deps.as_mut().storage);
+
+    transfer(&mut deps, "alice", "bob", 400);
+    transfer(&mut deps, "bob", "carol", 450);
+}
+
+#[test]
+fn transfers_after_migration_and_reindex_rebuild_stats() {
+    let mut deps = setup(&[("alice", 1_000), ("bob", 50)]);
+    let expected = query_stats(&deps);
+    make_legacy(&mut deps);
+
+    let resp = migrate(deps.as_mut(), mock_env(), empty_migrate_msg()).unwrap();
+    assert!(resp.attributes.iter().any(|a| a.key == "reindex" && a.value == "true"));
+    assert!(REINDEX_STATE.exists(deps.as_ref().storage));
+
+    // Balance changes during the reindex neither fail nor touch the stats
+    transfer(&mut deps, "alice", "carol", 300);
+    transfer(&mut deps, "carol", "alice", 300);
+
+    reindex(&mut deps, 1);
+    assert!(!REINDEX_STATE.exists(deps.as_ref().storage));
+
+    let stats = query_stats(&deps);
+    assert_eq!(stats.hhi, expected.hhi);
+    assert_eq!(stats.histogram, expected.histogram);
+    assert_eq!(stats.n_holders, expected.n_holders);
+
+    transfer(&mut deps, "alice", "bob", 950);
+    let stats = query_stats(&deps);
+    assert_eq!(stats.n_holders.u64(), 2);
+    assert_eq!(stats.histogram.len(), 2);
+}
+
+#[test]
+fn migrate_runs_upgrades_from_older_versions_only() {
+    let mut deps = setup(&[("alice", 1_000)]);
+    make_legacy(&mut deps);
+
+    let resp = migrate(deps.as_mut(), mock_env(), empty_migrate_msg()).unwrap();
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|a| a.key == "upgrades" && a.value == "0.1.0"));
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    reindex(&mut deps, 1);
+
+    // Migrating again to the same version doesn't repeat the upgrade
+    let resp = migrate(deps.as_mut(), mock_env(), empty_migrate_msg()).unwrap();
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|a| a.key == "upgrades" && a.value.is_empty()));
+    assert!(!REINDEX_STATE.exists(deps.as_ref().storage));
+}
+
+#[test]
+fn migrate_refuses_other_contracts_and_downgrades() {
+    let mut deps = setup(&[("alice", 1_000)]);
+
+    set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "1.1.2").unwrap();
+    migrate(deps.as_mut(), mock_env(), empty_migrate_msg()).unwrap_err();
+
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+    migrate(deps.as_mut(), mock_env(), empty_migrate_msg()).unwrap_err();
+
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "not-a-version").unwrap();
+    migrate(deps.as_mut(), mock_env(), empty_migrate_msg()).unwrap_err();
+}
+
+#[test]
+fn migrate_can_hand_over_the_operator() {
+    let mut deps = setup(&[("alice", 1_000)]);
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            operator: Some(Addr::unchecked("new_operator")),
+            ..empty_migrate_msg()
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OPERATOR, &[]),
+        ExecuteMsg::Pro(OperatorExecuteMsg::SetTransferHistorySize { size: 10 }),
+    )
+    .unwrap_err();
+}
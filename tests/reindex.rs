@@ -0,0 +1,87 @@
+mod common;
+
+use common::{is_done, operator_execute, query_pro, setup, transfer, Deps};
+use cosmwasm_std::{Addr, Uint64};
+use cw20_pro::{
+    msg::{BalancesResponse, HolderCountResponse, OperatorExecuteMsg, ProBalanceQueryMsg, ProQueryMsg, ReindexPhase},
+    state::{N_BALANCES, ORDERED_BALANCES, REINDEX_STATE},
+};
+
+fn reindex_batch(deps: &mut Deps) -> bool {
+    is_done(&operator_execute(deps, OperatorExecuteMsg::Reindex { limit: Some(2) }).unwrap())
+}
+
+fn ranked_balances(deps: &Deps) -> Vec<(String, u128)> {
+    let resp: BalancesResponse = query_pro(
+        deps,
+        ProQueryMsg::Balances(ProBalanceQueryMsg::All {
+            limit: None,
+            desc: Some(true),
+            cursor: None,
+            min_amount: None,
+            max_amount: None,
+            exclude: None,
+            include_excluded: None,
+        }),
+    )
+    .unwrap();
+    resp.balances
+        .into_iter()
+        .map(|b| (b.address.into_string(), b.amount.u128()))
+        .collect()
+}
+
+#[test]
+fn reindex_resumes_across_batches() {
+    let mut deps = setup(&[("alice", 50), ("bob", 40), ("carol", 30), ("dave", 20), ("erin", 10)]);
+
+    // Drift the index and holder count away from the balances
+    ORDERED_BALANCES.remove(&mut deps.storage, (50, &Addr::unchecked("alice")));
+    ORDERED_BALANCES
+        .save(&mut deps.storage, (999, &Addr::unchecked("ghost")), &0)
+        .unwrap();
+    N_BALANCES.save(&mut deps.storage, &Uint64::new(42)).unwrap();
+
+    // Clearing the 5 remaining index entries takes 3 batches of 2
+    for n_cleared in [2, 4] {
+        assert!(!reindex_batch(&mut deps));
+        let state = REINDEX_STATE.load(&deps.storage).unwrap();
+        assert_eq!(state.phase, ReindexPhase::Clearing);
+        assert_eq!(state.n_cleared, n_cleared);
+    }
+    assert!(!reindex_batch(&mut deps));
+    assert_eq!(REINDEX_STATE.load(&deps.storage).unwrap().phase, ReindexPhase::Indexing);
+
+    // Ranked queries wait for the rebuild
+    query_pro::<HolderCountResponse>(&deps, ProQueryMsg::HolderCount {}).unwrap_err();
+
+    assert!(!reindex_batch(&mut deps));
+    let state = REINDEX_STATE.load(&deps.storage).unwrap();
+    assert_eq!(state.n_indexed, 2);
+    assert_eq!(state.cursor, Some(Addr::unchecked("bob")));
+
+    // Balances that change mid-rebuild, on either side of the cursor, are
+    // picked up without being indexed twice
+    transfer(&mut deps, "alice", "frank", 50);
+    transfer(&mut deps, "erin", "bob", 5);
+
+    let mut n_calls = 0;
+    while !reindex_batch(&mut deps) {
+        n_calls += 1;
+    }
+    assert!(n_calls >= 1);
+    assert!(!REINDEX_STATE.exists(&deps.storage));
+
+    let holder_count: HolderCountResponse = query_pro(&deps, ProQueryMsg::HolderCount {}).unwrap();
+    assert_eq!(holder_count.n_holders, Uint64::new(5));
+    assert_eq!(
+        ranked_balances(&deps),
+        vec![
+            ("frank".to_owned(), 50),
+            ("bob".to_owned(), 45),
+            ("carol".to_owned(), 30),
+            ("dave".to_owned(), 20),
+            ("erin".to_owned(), 5),
+        ]
+    );
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{operator_execute, query_pro, setup, transfer, Deps};
+use cosmwasm_std::{Addr, Decimal, Uint128, Uint64};
+use cw20_pro::msg::{HistogramBucket, HolderCountResponse, OperatorExecuteMsg, ProQueryMsg, StatsResponse};
+
+fn query_stats(
+    deps: &Deps,
+    include_excluded: Option<bool>,
+) -> StatsResponse {
+    query_pro(deps, ProQueryMsg::Stats { include_excluded }).unwrap()
+}
+
+fn bucket(
+    min_balance: u128,
+    n_holders: u64,
+    amount: u128,
+) -> HistogramBucket {
+    HistogramBucket {
+        min_balance: Uint128::new(min_balance),
+        n_holders: Uint64::new(n_holders),
+        amount: Uint128::new(amount),
+    }
+}
+
+#[test]
+fn stats_follow_transfers() {
+    let mut deps = setup(&[("alice", 600), ("bob", 300), ("carol", 100)]);
+
+    let stats = query_stats(&deps, None);
+    assert_eq!(stats.n_holders, Uint64::new(3));
+    assert_eq!(stats.total_supply, Uint128::new(1_000));
+    assert_eq!(stats.top_10_share, Decimal::one());
+    // 0.6^2 + 0.3^2 + 0.1^2
+    assert_eq!(stats.hhi, Decimal::percent(46));
+    assert_eq!(stats.histogram, vec![bucket(100, 3, 1_000)]);
+
+    transfer(&mut deps, "alice", "dave", 595);
+    transfer(&mut deps, "carol", "bob", 100);
+
+    // alice 5, bob 400, dave 595
+    let stats = query_stats(&deps, None);
+    assert_eq!(stats.n_holders, Uint64::new(3));
+    assert_eq!(
+        stats.hhi,
+        Decimal::from_ratio(25u128 + 160_000 + 354_025, 1_000_000u128)
+    );
+    assert_eq!(stats.histogram, vec![bucket(1, 1, 5), bucket(100, 2, 995)]);
+}
+
+#[test]
+fn stats_leave_out_excluded_accounts_by_default() {
+    let mut deps = setup(&[("alice", 600), ("bob", 300), ("carol", 100)]);
+    operator_execute(
+        &mut deps,
+        OperatorExecuteMsg::UpdateExcludedAccounts {
+            add: Some(vec![Addr::unchecked("alice")]),
+            remove: None,
+        },
+    )
+    .unwrap();
+
+    // Measured against the 400 circulating: 0.75^2 + 0.25^2
+    let stats = query_stats(&deps, None);
+    let holder_count: HolderCountResponse = query_pro(&deps, ProQueryMsg::HolderCount {}).unwrap();
+    assert_eq!(stats.n_holders, holder_count.n_holders);
+    assert_eq!(stats.n_holders, Uint64::new(2));
+    assert_eq!(stats.total_supply, Uint128::new(400));
+    assert_eq!(stats.hhi, Decimal::permille(625));
+    assert_eq!(stats.top_10_share, Decimal::one());
+    assert_eq!(stats.histogram, vec![bucket(100, 2, 400)]);
+
+    let stats = query_stats(&deps, Some(true));
+    assert_eq!(stats.n_holders, Uint64::new(3));
+    assert_eq!(stats.total_supply, Uint128::new(1_000));
+    assert_eq!(stats.hhi, Decimal::percent(46));
+    assert_eq!(stats.histogram, vec![bucket(100, 3, 1_000)]);
+}
+
+#[test]
+fn top_shares_skip_excluded_accounts() {
+    let names: Vec<String> = (1..=12).map(|i| format!("holder{:02}", i)).collect();
+    let balances: Vec<(&str, u128)> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), (i as u128 + 1) * 10))
+        .collect();
+    let mut deps = setup(&balances);
+
+    // The two smallest of the 780 total are outside the top 10
+    let stats = query_stats(&deps, None);
+    assert_eq!(stats.top_10_share, Decimal::from_ratio(750u128, 780u128));
+    assert_eq!(stats.top_100_share, Decimal::one());
+
+    // Without the largest holder, the next 10 make up the top 10
+    operator_execute(
+        &mut deps,
+        OperatorExecuteMsg::UpdateExcludedAccounts {
+            add: Some(vec![Addr::unchecked("holder12")]),
+            remove: None,
+        },
+    )
+    .unwrap();
+    let stats = query_stats(&deps, None);
+    assert_eq!(stats.top_10_share, Decimal::from_ratio(650u128, 660u128));
+    assert_eq!(stats.top_100_share, Decimal::one());
+}
@@ -0,0 +1,384 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    WasmMsg,
+};
+use cw20::Cw20Coin;
+use cw20_pro::{
+    contract::{execute, instantiate, query, reply},
+    msg::{
+        BalanceChangeEvent, ExecuteMsg, ListenerHealth, ListenerQueueResponse, OperatorExecuteMsg, ProQueryMsg,
+        QueryMsg, SubscriptionResponse,
+    },
+};
+use cw_multi_test::{error::AnyResult, App, AppResponse, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+
+const OPERATOR: &str = "operator";
+
+/// Number of events the mock subscriber has received
+const N_RECEIVED: Item<u32> = Item::new("n_received");
+/// Whether the mock subscriber fails every callback
+const FAILING: Item<bool> = Item::new("failing");
+
+#[cw_serde]
+enum SubscriberExecuteMsg {
+    Subscribe { token: Addr, watched: Vec<Addr> },
+    Unsubscribe { token: Addr },
+    SetFailing { failing: bool },
+    OnBalanceChange { event: BalanceChangeEvent },
+}
+
+fn subscriber_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    N_RECEIVED.save(deps.storage, &0)?;
+    FAILING.save(deps.storage, &false)?;
+    Ok(Response::new())
+}
+
+fn subscriber_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: SubscriberExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        SubscriberExecuteMsg::Subscribe { token, watched } => Ok(Response::new().add_message(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&ExecuteMsg::Subscribe {
+                watched,
+                event_version: None,
+                gas_limit: 200_000,
+            })?,
+            funds: vec![],
+        })),
+        SubscriberExecuteMsg::Unsubscribe { token } => Ok(Response::new().add_message(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&ExecuteMsg::Unsubscribe {})?,
+            funds: vec![],
+        })),
+        SubscriberExecuteMsg::SetFailing { failing } => {
+            FAILING.save(deps.storage, &failing)?;
+            Ok(Response::new())
+        },
+        SubscriberExecuteMsg::OnBalanceChange { .. } => {
+            if FAILING.load(deps.storage)? {
+                return Err(StdError::generic_err("subscriber is failing"));
+            }
+            N_RECEIVED.update(deps.storage, |n| -> StdResult<_> { Ok(n + 1) })?;
+            Ok(Response::new())
+        },
+    }
+}
+
+fn subscriber_query(
+    deps: Deps,
+    _env: Env,
+    _msg: Empty,
+) -> StdResult<Binary> {
+    to_json_binary(&N_RECEIVED.load(deps.storage)?)
+}
+
+struct Suite {
+    app: App,
+    token: Addr,
+    subscriber: Addr,
+}
+
+impl Suite {
+    fn new() -> Self {
+        let mut app = App::default();
+        let token_code_id = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+        ));
+        let subscriber_code_id = app.store_code(Box::new(ContractWrapper::new(
+            subscriber_execute,
+            subscriber_instantiate,
+            subscriber_query,
+        )));
+
+        let token = app
+            .instantiate_contract(
+                token_code_id,
+                Addr::unchecked(OPERATOR),
+                &cw20_base::msg::InstantiateMsg {
+                    name: "Token".to_owned(),
+                    symbol: "TKN".to_owned(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: "alice".to_owned(),
+                        amount: Uint128::new(1_000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+        let subscriber = app
+            .instantiate_contract(
+                subscriber_code_id,
+                Addr::unchecked(OPERATOR),
+                &Empty {},
+                &[],
+                "subscriber",
+                None,
+            )
+            .unwrap();
+
+        let mut suite = Self { app, token, subscriber };
+        suite.subscriber_execute(SubscriberExecuteMsg::Subscribe {
+            token: suite.token.to_owned(),
+            watched: vec![Addr::unchecked("alice")],
+        });
+        suite
+    }
+
+    fn subscriber_execute(
+        &mut self,
+        msg: SubscriberExecuteMsg,
+    ) {
+        self.try_subscriber_execute(msg).unwrap();
+    }
+
+    fn try_subscriber_execute(
+        &mut self,
+        msg: SubscriberExecuteMsg,
+    ) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(OPERATOR), self.subscriber.to_owned(), &msg, &[])
+    }
+
+    fn token_execute(
+        &mut self,
+        sender: &str,
+        msg: &ExecuteMsg,
+    ) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.token.to_owned(), msg, &[])
+    }
+
+    fn transfer(&mut self) {
+        self.token_execute(
+            "alice",
+            &ExecuteMsg::Transfer {
+                recipient: "bob".to_owned(),
+                amount: Uint128::one(),
+                memo: None,
+            },
+        )
+        .unwrap();
+    }
+
+    fn n_received(&self) -> u32 {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.subscriber.to_owned(), &Empty {})
+            .unwrap()
+    }
+
+    fn subscription(&self) -> SubscriptionResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                self.token.to_owned(),
+                &QueryMsg::Pro(ProQueryMsg::Subscription {
+                    subscriber: self.subscriber.to_owned(),
+                }),
+            )
+            .unwrap()
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.subscription().health
+    }
+
+    fn n_queued(&self) -> usize {
+        let resp: ListenerQueueResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.token.to_owned(),
+                &QueryMsg::Pro(ProQueryMsg::ListenerQueue {
+                    listener: self.subscriber.to_owned(),
+                    limit: Some(100),
+                    cursor: None,
+                }),
+            )
+            .unwrap();
+        resp.events.len()
+    }
+
+    fn retry(&mut self) -> AnyResult<AppResponse> {
+        self.token_execute(
+            "anyone",
+            &ExecuteMsg::RetryBalanceChangeNotifications {
+                listener: self.subscriber.to_owned(),
+                limit: None,
+            },
+        )
+    }
+}
+
+#[test]
+fn subscriber_is_disabled_after_repeated_failures_and_retried_once_enabled() {
+    let mut suite = Suite::new();
+
+    suite.transfer();
+    assert_eq!(suite.n_received(), 1);
+
+    // Failed callbacks don't revert transfers, and are queued for retry
+    suite.subscriber_execute(SubscriberExecuteMsg::SetFailing { failing: true });
+    for n in 1..=5 {
+        suite.transfer();
+        assert_eq!(suite.health().n_failures, n);
+    }
+    let health = suite.health();
+    assert!(health.disabled);
+    assert_eq!(health.total_failures, 5);
+    assert_eq!(health.total_deliveries, 6);
+    assert_eq!(suite.n_queued(), 5);
+
+    // Disabled subscribers are neither called nor queued for
+    suite.transfer();
+    assert_eq!(suite.health().total_deliveries, 6);
+    assert_eq!(suite.n_queued(), 5);
+    suite.retry().unwrap_err();
+
+    // Subscribing again doesn't re-enable the subscriber
+    suite.subscriber_execute(SubscriberExecuteMsg::Subscribe {
+        token: suite.token.to_owned(),
+        watched: vec![Addr::unchecked("alice")],
+    });
+    assert!(suite.health().disabled);
+
+    let subscriber = suite.subscriber.to_owned();
+    suite
+        .token_execute(
+            OPERATOR,
+            &ExecuteMsg::Pro(OperatorExecuteMsg::EnableBalanceChangeListener { address: subscriber }),
+        )
+        .unwrap();
+    suite.subscriber_execute(SubscriberExecuteMsg::SetFailing { failing: false });
+
+    suite.retry().unwrap();
+    assert_eq!(suite.n_received(), 6);
+    assert_eq!(suite.n_queued(), 0);
+    assert!(!suite.health().disabled);
+}
+
+#[test]
+fn successful_callbacks_break_a_run_of_failures() {
+    let mut suite = Suite::new();
+
+    suite.subscriber_execute(SubscriberExecuteMsg::SetFailing { failing: true });
+    suite.transfer();
+    suite.transfer();
+    assert_eq!(suite.health().n_failures, 2);
+
+    suite.subscriber_execute(SubscriberExecuteMsg::SetFailing { failing: false });
+    suite.transfer();
+    suite.subscriber_execute(SubscriberExecuteMsg::SetFailing { failing: true });
+    suite.transfer();
+
+    let health = suite.health();
+    assert_eq!(health.n_failures, 1);
+    assert_eq!(health.total_failures, 3);
+    assert!(!health.disabled);
+}
+
+#[test]
+fn unsubscribe_discards_queued_events_and_keeps_health() {
+    let mut suite = Suite::new();
+
+    suite.subscriber_execute(SubscriberExecuteMsg::SetFailing { failing: true });
+    suite.transfer();
+    suite.transfer();
+    assert_eq!(suite.n_queued(), 2);
+
+    suite.subscriber_execute(SubscriberExecuteMsg::Unsubscribe {
+        token: suite.token.to_owned(),
+    });
+    assert_eq!(suite.n_queued(), 0);
+
+    let resp = suite.subscription();
+    assert!(resp.subscription.is_none());
+    assert_eq!(resp.health.total_failures, 2);
+}
+
+#[test]
+fn watched_account_and_operator_can_evict_subscribers() {
+    let mut suite = Suite::new();
+    let subscriber = suite.subscriber.to_owned();
+    suite.subscriber_execute(SubscriberExecuteMsg::Subscribe {
+        token: suite.token.to_owned(),
+        watched: vec![Addr::unchecked("alice"), Addr::unchecked("bob")],
+    });
+
+    let evict = ExecuteMsg::EvictSubscriber {
+        subscriber: subscriber.to_owned(),
+    };
+    suite.token_execute("carol", &evict).unwrap_err();
+
+    // A watched account only removes itself
+    suite.token_execute("alice", &evict).unwrap();
+    let subscription = suite.subscription().subscription.unwrap();
+    assert_eq!(subscription.watched, vec![Addr::unchecked("bob")]);
+
+    // Transfers from alice still reach the subscriber through bob
+    suite.transfer();
+    assert_eq!(suite.n_received(), 1);
+
+    // The operator cancels the whole subscription
+    suite.token_execute(OPERATOR, &evict).unwrap();
+    assert!(suite.subscription().subscription.is_none());
+    suite.transfer();
+    assert_eq!(suite.n_received(), 1);
+}
+
+#[test]
+fn evicted_subscriber_cant_watch_again_until_allowed() {
+    let mut suite = Suite::new();
+    let subscriber = suite.subscriber.to_owned();
+    let watch_alice = SubscriberExecuteMsg::Subscribe {
+        token: suite.token.to_owned(),
+        watched: vec![Addr::unchecked("alice")],
+    };
+
+    suite
+        .token_execute(
+            "alice",
+            &ExecuteMsg::EvictSubscriber {
+                subscriber: subscriber.to_owned(),
+            },
+        )
+        .unwrap();
+    assert!(suite.subscription().subscription.is_none());
+
+    // Neither a new subscription nor one that adds alice back is accepted
+    suite.try_subscriber_execute(watch_alice.to_owned()).unwrap_err();
+    suite
+        .try_subscriber_execute(SubscriberExecuteMsg::Subscribe {
+            token: suite.token.to_owned(),
+            watched: vec![Addr::unchecked("bob"), Addr::unchecked("alice")],
+        })
+        .unwrap_err();
+    suite.transfer();
+    assert_eq!(suite.n_received(), 0);
+
+    // Only alice can allow it again
+    let allow = ExecuteMsg::AllowSubscriber {
+        subscriber: subscriber.to_owned(),
+    };
+    suite.token_execute(OPERATOR, &allow).unwrap_err();
+    suite.token_execute("alice", &allow).unwrap();
+    suite.subscriber_execute(watch_alice);
+    suite.transfer();
+    assert_eq!(suite.n_received(), 1);
+}
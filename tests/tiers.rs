@@ -0,0 +1,172 @@
+mod common;
+
+use common::{operator_execute, query_pro, reindex, setup, transfer, Deps};
+use cosmwasm_std::{Addr, Uint128, Uint64};
+use cw20_pro::msg::{
+    AccountTierResponse, BalancesResponse, HolderTier, OperatorExecuteMsg, ProQueryMsg, TiersResponse,
+};
+
+/// Bronze from 100 up to, but not including, silver at 1,000
+fn setup_tiers(balances: &[(&str, u128)]) -> Deps {
+    let mut deps = setup(balances);
+    operator_execute(
+        &mut deps,
+        OperatorExecuteMsg::SetTiers {
+            tiers: vec![
+                HolderTier {
+                    name: "silver".to_owned(),
+                    min_balance: Uint128::new(1_000),
+                },
+                HolderTier {
+                    name: "bronze".to_owned(),
+                    min_balance: Uint128::new(100),
+                },
+            ],
+        },
+    )
+    .unwrap();
+    reindex(&mut deps, 2);
+    deps
+}
+
+fn tier_counts(
+    deps: &Deps,
+    include_excluded: Option<bool>,
+) -> Vec<(String, Uint64)> {
+    let resp: TiersResponse = query_pro(deps, ProQueryMsg::Tiers { include_excluded }).unwrap();
+    resp.tiers.into_iter().map(|t| (t.tier.name, t.n_holders)).collect()
+}
+
+fn tier_of(
+    deps: &Deps,
+    address: &str,
+) -> Option<String> {
+    let resp: AccountTierResponse = query_pro(
+        deps,
+        ProQueryMsg::Tier {
+            address: Addr::unchecked(address),
+        },
+    )
+    .unwrap();
+    resp.tier.map(|t| t.name)
+}
+
+fn tier_holders(
+    deps: &Deps,
+    tier: &str,
+    include_excluded: Option<bool>,
+) -> Vec<(String, u128)> {
+    let resp: BalancesResponse = query_pro(
+        deps,
+        ProQueryMsg::TierHolders {
+            tier: tier.to_owned(),
+            limit: None,
+            desc: None,
+            cursor: None,
+            include_excluded,
+        },
+    )
+    .unwrap();
+    resp.balances
+        .into_iter()
+        .map(|b| (b.address.into_string(), b.amount.u128()))
+        .collect()
+}
+
+#[test]
+fn tier_thresholds_are_inclusive_lower_bounds() {
+    let mut deps = setup_tiers(&[("alice", 1_000), ("bob", 999), ("carol", 100), ("dave", 99)]);
+
+    assert_eq!(
+        tier_counts(&deps, None),
+        vec![
+            ("bronze".to_owned(), Uint64::new(2)),
+            ("silver".to_owned(), Uint64::new(1))
+        ]
+    );
+    assert_eq!(tier_of(&deps, "alice"), Some("silver".to_owned()));
+    assert_eq!(tier_of(&deps, "bob"), Some("bronze".to_owned()));
+    assert_eq!(tier_of(&deps, "carol"), Some("bronze".to_owned()));
+    assert_eq!(tier_of(&deps, "dave"), None);
+    assert_eq!(
+        tier_holders(&deps, "bronze", None),
+        vec![("bob".to_owned(), 999), ("carol".to_owned(), 100)]
+    );
+
+    // One token each way moves alice down to bronze and dave up into it
+    transfer(&mut deps, "alice", "dave", 1);
+    assert_eq!(
+        tier_counts(&deps, None),
+        vec![
+            ("bronze".to_owned(), Uint64::new(4)),
+            ("silver".to_owned(), Uint64::zero())
+        ]
+    );
+    assert_eq!(tier_of(&deps, "dave"), Some("bronze".to_owned()));
+    assert!(tier_holders(&deps, "silver", None).is_empty());
+
+    // Emptying a balance leaves the tiers altogether
+    transfer(&mut deps, "carol", "bob", 100);
+    assert_eq!(tier_of(&deps, "carol"), None);
+    assert_eq!(tier_of(&deps, "bob"), Some("silver".to_owned()));
+    assert_eq!(
+        tier_counts(&deps, None),
+        vec![
+            ("bronze".to_owned(), Uint64::new(2)),
+            ("silver".to_owned(), Uint64::one())
+        ]
+    );
+}
+
+#[test]
+fn tier_counts_leave_out_excluded_accounts_by_default() {
+    let mut deps = setup_tiers(&[("alice", 1_000), ("bob", 999), ("carol", 100)]);
+    operator_execute(
+        &mut deps,
+        OperatorExecuteMsg::UpdateExcludedAccounts {
+            add: Some(vec![Addr::unchecked("bob")]),
+            remove: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        tier_counts(&deps, None),
+        vec![
+            ("bronze".to_owned(), Uint64::one()),
+            ("silver".to_owned(), Uint64::one())
+        ]
+    );
+    assert_eq!(tier_holders(&deps, "bronze", None), vec![("carol".to_owned(), 100)]);
+
+    assert_eq!(
+        tier_counts(&deps, Some(true)),
+        vec![
+            ("bronze".to_owned(), Uint64::new(2)),
+            ("silver".to_owned(), Uint64::one())
+        ]
+    );
+    assert_eq!(
+        tier_holders(&deps, "bronze", Some(true)),
+        vec![("bob".to_owned(), 999), ("carol".to_owned(), 100)]
+    );
+}
+
+#[test]
+fn tier_queries_wait_for_the_recount() {
+    let mut deps = setup(&[("alice", 1_000), ("bob", 999), ("carol", 100)]);
+    operator_execute(
+        &mut deps,
+        OperatorExecuteMsg::SetTiers {
+            tiers: vec![HolderTier {
+                name: "bronze".to_owned(),
+                min_balance: Uint128::new(100),
+            }],
+        },
+    )
+    .unwrap();
+    query_pro::<TiersResponse>(&deps, ProQueryMsg::Tiers { include_excluded: None }).unwrap_err();
+
+    reindex(&mut deps, 1);
+    assert_eq!(tier_counts(&deps, None), vec![("bronze".to_owned(), Uint64::new(3))]);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{execute_as, operator_execute, query_pro, setup, Deps};
+use cosmwasm_std::{testing::mock_env, Addr, Int128, Order, Uint128};
+use cw20_pro::{
+    msg::{ExecuteMsg, OperatorExecuteMsg, ProQueryMsg, TransferHistoryResponse},
+    state::TRANSFER_HISTORY,
+};
+
+fn send(
+    deps: &mut Deps,
+    amount: u128,
+) {
+    execute_as(
+        deps,
+        mock_env(),
+        "alice",
+        ExecuteMsg::Transfer {
+            recipient: "bob".to_owned(),
+            amount: Uint128::new(amount),
+            memo: Some(format!("#{}", amount)),
+        },
+    )
+    .unwrap();
+}
+
+fn set_size(
+    deps: &mut Deps,
+    size: u16,
+) {
+    operator_execute(deps, OperatorExecuteMsg::SetTransferHistorySize { size }).unwrap();
+}
+
+fn history(
+    deps: &Deps,
+    address: &str,
+    limit: Option<u16>,
+    cursor: Option<u32>,
+) -> TransferHistoryResponse {
+    query_pro(
+        deps,
+        ProQueryMsg::TransferHistory {
+            address: Addr::unchecked(address),
+            limit,
+            cursor,
+        },
+    )
+    .unwrap()
+}
+
+fn amounts(resp: &TransferHistoryResponse) -> Vec<i128> {
+    resp.transfers
+        .iter()
+        .map(|t| Int128::try_from(t.amount).unwrap().i128())
+        .collect()
+}
+
+/// Number of records kept in storage for the account
+fn n_stored(
+    deps: &Deps,
+    address: &str,
+) -> usize {
+    TRANSFER_HISTORY
+        .prefix(&Addr::unchecked(address))
+        .keys(&deps.storage, None, None, Order::Ascending)
+        .count()
+}
+
+#[test]
+fn transfer_history_wraps_around() {
+    let mut deps = setup(&[("alice", 1_000)]);
+    set_size(&mut deps, 3);
+    for amount in 1..=5 {
+        send(&mut deps, amount);
+    }
+
+    // Only the 3 most recent transfers are kept, newest first
+    let resp = history(&deps, "alice", None, None);
+    assert_eq!(amounts(&resp), vec![-5, -4, -3]);
+    assert_eq!(resp.transfers[0].memo, Some("#5".to_owned()));
+    assert_eq!(resp.transfers[0].counterparty, Addr::unchecked("bob"));
+    assert!(resp.cursor.is_none());
+    assert_eq!(amounts(&history(&deps, "bob", None, None)), vec![5, 4, 3]);
+    assert_eq!(n_stored(&deps, "alice"), 3);
+    assert_eq!(n_stored(&deps, "bob"), 3);
+
+    let page = history(&deps, "alice", Some(2), None);
+    assert_eq!(amounts(&page), vec![-5, -4]);
+    let page = history(&deps, "alice", Some(2), page.cursor);
+    assert_eq!(amounts(&page), vec![-3]);
+    assert!(page.cursor.is_none());
+}
+
+#[test]
+fn resizing_transfer_history() {
+    let mut deps = setup(&[("alice", 1_000)]);
+    set_size(&mut deps, 3);
+    for amount in 1..=3 {
+        send(&mut deps, amount);
+    }
+
+    // Shrinking hides older entries right away, and drops them on the next
+    // transfer
+    set_size(&mut deps, 2);
+    assert_eq!(amounts(&history(&deps, "alice", None, None)), vec![-3, -2]);
+    send(&mut deps, 4);
+    assert_eq!(amounts(&history(&deps, "alice", None, None)), vec![-4, -3]);
+    assert_eq!(n_stored(&deps, "alice"), 2);
+
+    // Turning it off clears an account's history on its next transfer
+    set_size(&mut deps, 0);
+    send(&mut deps, 5);
+    assert!(history(&deps, "alice", None, None).transfers.is_empty());
+    assert_eq!(n_stored(&deps, "alice"), 0);
+    assert_eq!(n_stored(&deps, "bob"), 0);
+}